@@ -1,90 +1,214 @@
-use std::process::Command;
+use std::path::Path;
 
 use crate::models::{AuthStatus, UsageResponse};
 use crate::platform;
-use crate::services::settings_store::{self, AppSettings, LayoutConfig, LayoutType, ShortcutConfig};
-use crate::services::{fetch_usage, get_credentials_path, is_token_valid, read_credentials};
-
-#[tauri::command]
-pub async fn check_credentials() -> Result<AuthStatus, String> {
-    let path = get_credentials_path();
-    let path_str = path.display().to_string();
-
-    match read_credentials() {
-        Ok(creds) => {
-            let is_valid = is_token_valid(&creds);
-            Ok(AuthStatus {
-                authenticated: is_valid,
-                expires_at: Some(creds.expires_at),
-                credentials_path: path_str,
-                error_reason: if is_valid {
-                    None
-                } else {
-                    Some("token_expired".to_string())
-                },
-            })
-        }
-        Err(e) => {
-            let error_reason = match e {
-                crate::services::CredentialError::NotFound => "not_found",
-                crate::services::CredentialError::NoOAuthCredentials => "no_oauth",
-                crate::services::CredentialError::ReadError(_) => "read_error",
-                crate::services::CredentialError::ParseError(_) => "parse_error",
-                crate::services::CredentialError::KeychainError(_) => "keychain_error",
-            };
-            Ok(AuthStatus {
-                authenticated: false,
-                expires_at: None,
-                credentials_path: path_str,
-                error_reason: Some(error_reason.to_string()),
-            })
-        }
-    }
+use crate::services::claude_cli::{self, ClaudeCliInfo};
+use crate::services::login_terminal::LoginTerminal;
+use crate::services::network::{self, ConnectionReport};
+use crate::services::oauth_login;
+use crate::services::settings_store::{
+    self, AppSettings, ExpiryWarningConfig, LayoutConfig, LayoutType, NetworkConfig, Profile,
+    SettingsRecovery, ShortcutConfig,
+};
+use crate::services::shortcut_manager::ShortcutManager;
+use crate::services::terminal_launcher;
+use crate::services::usage_scheduler::{self, UsageState};
+use crate::services::{accelerator, app_dirs};
+use crate::services::{
+    api_base_url, auth_status, get_credentials_path, restrict_credentials_permissions,
+};
+
+/// Profile `profile_id`, or the active profile when `None`
+fn find_profile(profile_id: Option<&str>) -> Result<Profile, String> {
+    settings_store::load_settings_or_default()
+        .profile(profile_id)
+        .ok_or_else(|| format!("Unknown profile: {}", profile_id.unwrap_or_default()))
 }
 
 #[tauri::command]
-pub async fn fetch_usage_data() -> Result<UsageResponse, String> {
-    let creds = read_credentials().map_err(|e| e.to_string())?;
+pub async fn check_credentials(profile_id: Option<String>) -> Result<AuthStatus, String> {
+    let profile = find_profile(profile_id.as_deref())?;
+    Ok(auth_status(&profile).await)
+}
 
-    if !is_token_valid(&creds) {
-        return Err("Token has expired. Please login again using 'claude' CLI.".to_string());
+/// Tighten the profile's credentials file to 0600. The frontend asks the user
+/// to confirm first; only the file reported in `AuthStatus` is ever touched.
+#[tauri::command]
+pub async fn fix_credentials_permissions(profile_id: Option<String>) -> Result<AuthStatus, String> {
+    let profile = find_profile(profile_id.as_deref())?;
+    let warning = auth_status(&profile)
+        .await
+        .permission_warning
+        .ok_or("The credentials file permissions are already safe")?;
+    if !warning.fixable {
+        return Err(format!(
+            "{} is owned by another user; fix its owner manually",
+            warning.path
+        ));
     }
 
-    let usage = fetch_usage(&creds.access_token)
+    restrict_credentials_permissions(Path::new(&warning.path)).map_err(|e| e.to_string())?;
+    Ok(auth_status(&profile).await)
+}
+
+/// Log the profile in through the browser, then fetch its usage right away
+/// instead of waiting for the next scheduled poll
+#[tauri::command]
+pub async fn start_oauth_login(
+    app: tauri::AppHandle,
+    profile_id: Option<String>,
+) -> Result<AuthStatus, String> {
+    let profile = find_profile(profile_id.as_deref())?;
+    oauth_login::login(&profile, |url| open::that(url).map_err(|e| e.to_string()))
         .await
         .map_err(|e| e.to_string())?;
 
-    Ok(usage)
+    let _ = usage_scheduler::poll_once(&app, &profile).await;
+    Ok(auth_status(&profile).await)
+}
+
+#[tauri::command]
+pub async fn fetch_usage_data(
+    app: tauri::AppHandle,
+    state: tauri::State<'_, UsageState>,
+    profile_id: Option<String>,
+) -> Result<UsageResponse, String> {
+    let profile = find_profile(profile_id.as_deref())?;
+    // Serve the scheduler's cache so extra windows don't each hit the API
+    if let Some(usage) = state.fresh(&profile.id) {
+        return Ok(usage);
+    }
+    match usage_scheduler::poll_once(&app, &profile).await {
+        Ok(usage) => Ok(usage),
+        // During an outage fall back to the last known usage, marked stale
+        Err(e) => state.latest(&profile.id).ok_or_else(|| e.to_string()),
+    }
+}
+
+#[derive(serde::Serialize)]
+pub struct ProfileList {
+    pub profiles: Vec<Profile>,
+    pub active_profile: String,
+}
+
+#[tauri::command]
+pub fn get_profiles() -> ProfileList {
+    let settings = settings_store::load_settings_or_default();
+    let profiles = settings.effective_profiles();
+    let active_profile = settings
+        .profile(None)
+        .map(|profile| profile.id)
+        .unwrap_or_default();
+    ProfileList {
+        profiles,
+        active_profile,
+    }
+}
+
+#[tauri::command]
+pub fn save_profiles(profiles: Vec<Profile>) -> Result<(), String> {
+    let mut ids = std::collections::HashSet::new();
+    for profile in &profiles {
+        if !settings_store::is_valid_profile_id(&profile.id) {
+            return Err(format!("Invalid profile id: {}", profile.id));
+        }
+        if !ids.insert(profile.id.as_str()) {
+            return Err(format!("Duplicate profile id: {}", profile.id));
+        }
+        if profile.name.trim().is_empty() {
+            return Err(format!("Profile {} has no name", profile.id));
+        }
+    }
+
+    let mut settings = settings_store::load_settings().map_err(|e| e.to_string())?;
+    if settings
+        .active_profile
+        .as_ref()
+        .is_some_and(|active| !ids.contains(active.as_str()))
+    {
+        settings.active_profile = None;
+    }
+    settings.profiles = profiles;
+    settings_store::save_settings(&settings).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn set_active_profile(profile_id: String) -> Result<(), String> {
+    let mut settings = settings_store::load_settings().map_err(|e| e.to_string())?;
+    if settings.profile(Some(&profile_id)).is_none() {
+        return Err(format!("Unknown profile: {}", profile_id));
+    }
+    settings.active_profile = Some(profile_id);
+    settings_store::save_settings(&settings).map_err(|e| e.to_string())
 }
 
 #[tauri::command]
-pub fn get_credentials_path_cmd() -> String {
-    get_credentials_path().display().to_string()
+pub fn get_credentials_path_cmd() -> Result<String, String> {
+    get_credentials_path()
+        .map(|path| path.display().to_string())
+        .map_err(|e| e.to_string())
 }
 
 #[tauri::command]
-pub fn get_settings() -> AppSettings {
-    settings_store::load_settings()
+pub fn save_credentials_path_setting(credentials_path: Option<String>) -> Result<(), String> {
+    let mut settings = settings_store::load_settings().map_err(|e| e.to_string())?;
+    settings.credentials_path = credentials_path
+        .map(|path| path.trim().to_string())
+        .filter(|path| !path.is_empty());
+    settings_store::save_settings(&settings).map_err(|e| e.to_string())
 }
 
 #[tauri::command]
-pub fn save_shortcut_setting(modifier: String, key: String) -> Result<(), String> {
-    let mut settings = settings_store::load_settings();
+pub fn get_settings() -> Result<AppSettings, String> {
+    settings_store::load_settings().map_err(|e| e.to_string())
+}
+
+/// Report, once, that a corrupt settings file was quarantined at startup
+#[tauri::command]
+pub fn take_settings_recovery() -> Option<SettingsRecovery> {
+    settings_store::take_settings_recovery()
+}
+
+/// Report, once, that there was nowhere to keep the app's files at startup
+#[tauri::command]
+pub fn take_missing_dirs() -> Option<String> {
+    app_dirs::take_missing_dirs()
+}
+
+/// Takes effect immediately; a shortcut that can't be registered is neither
+/// bound nor saved
+#[tauri::command]
+pub fn save_shortcut_setting(
+    app: tauri::AppHandle,
+    shortcuts: tauri::State<'_, ShortcutManager>,
+    modifier: String,
+    key: String,
+) -> Result<(), String> {
+    let mut settings = settings_store::load_settings().map_err(|e| e.to_string())?;
+    let previous = settings.shortcut.clone();
     settings.shortcut = ShortcutConfig { modifier, key };
     settings.first_launch = false;
-    settings_store::save_settings(&settings).map_err(|e| e.to_string())
+
+    shortcuts
+        .apply(&app, &settings.shortcut)
+        .map_err(|e| e.to_string())?;
+    settings_store::save_settings(&settings).map_err(|e| {
+        // Keep the binding in line with the file
+        let _ = shortcuts.apply(&app, &previous);
+        e.to_string()
+    })
 }
 
 #[tauri::command]
 pub fn complete_first_launch() -> Result<(), String> {
-    let mut settings = settings_store::load_settings();
+    let mut settings = settings_store::load_settings().map_err(|e| e.to_string())?;
     settings.first_launch = false;
     settings_store::save_settings(&settings).map_err(|e| e.to_string())
 }
 
 #[tauri::command]
 pub fn save_layout_setting(layout_type: String) -> Result<(), String> {
-    let mut settings = settings_store::load_settings();
+    let mut settings = settings_store::load_settings().map_err(|e| e.to_string())?;
     settings.layout = LayoutConfig {
         layout_type: match layout_type.as_str() {
             "detailed" => LayoutType::Detailed,
@@ -94,12 +218,57 @@ pub fn save_layout_setting(layout_type: String) -> Result<(), String> {
     settings_store::save_settings(&settings).map_err(|e| e.to_string())
 }
 
+#[tauri::command]
+pub fn save_api_base_url_setting(api_base_url: Option<String>) -> Result<(), String> {
+    let api_base_url = api_base_url
+        .map(|url| url.trim().to_string())
+        .filter(|url| !url.is_empty());
+
+    if let Some(url) = &api_base_url {
+        if !url.starts_with("http://") && !url.starts_with("https://") {
+            return Err(format!("Invalid API base URL: {}", url));
+        }
+    }
+
+    let mut settings = settings_store::load_settings().map_err(|e| e.to_string())?;
+    settings.api_base_url = api_base_url;
+    settings_store::save_settings(&settings).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn save_network_setting(network: NetworkConfig) -> Result<(), String> {
+    let mut settings = settings_store::load_settings().map_err(|e| e.to_string())?;
+    settings.network = network;
+    settings_store::save_settings(&settings).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn save_expiry_warning_setting(mut expiry_warnings: ExpiryWarningConfig) -> Result<(), String> {
+    // Warn for the earliest threshold first; zero would only fire after expiry
+    expiry_warnings
+        .minutes_before
+        .retain(|minutes| *minutes > 0);
+    expiry_warnings
+        .minutes_before
+        .sort_unstable_by(|a, b| b.cmp(a));
+    expiry_warnings.minutes_before.dedup();
+
+    let mut settings = settings_store::load_settings().map_err(|e| e.to_string())?;
+    settings.expiry_warnings = expiry_warnings;
+    settings_store::save_settings(&settings).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn test_connection() -> ConnectionReport {
+    network::test_connection(&api_base_url()).await
+}
+
 #[tauri::command]
 pub fn get_platform_info() -> PlatformInfo {
-    let settings = settings_store::load_settings();
+    let settings = settings_store::load_settings_or_default();
     PlatformInfo {
         name: platform::get_platform_name().to_string(),
-        shortcut_display: format_shortcut_display(&settings.shortcut),
+        shortcut_display: accelerator::format_shortcut_display(&settings.shortcut),
         transparency_supported: platform::check_transparency_support(),
         is_first_launch: settings.first_launch,
     }
@@ -136,117 +305,79 @@ pub fn set_window_size(window: tauri::Window, width: u32, height: u32) -> Result
         .map_err(|e| e.to_string())
 }
 
+/// Run the `claude` login inside the app; returns the session id carried by
+/// its `login-terminal-output` and `login-terminal-exit` events
 #[tauri::command]
-pub fn launch_claude_cli() -> Result<(), String> {
-    #[cfg(target_os = "macos")]
-    {
-        // On macOS, open Terminal with a specific title for later identification
-        let script = r#"tell application "Terminal"
-            activate
-            set newTab to do script "claude"
-            set custom title of newTab to "Claude Login"
-        end tell"#;
-
-        Command::new("osascript")
-            .arg("-e")
-            .arg(script)
-            .spawn()
-            .map_err(|e| format!("Failed to open Terminal: {}", e))?;
-
-        return Ok(());
-    }
+pub async fn start_login_terminal(
+    app: tauri::AppHandle,
+    terminal: tauri::State<'_, LoginTerminal>,
+    profile_id: Option<String>,
+    cols: u16,
+    rows: u16,
+) -> Result<u64, String> {
+    let profile = find_profile(profile_id.as_deref())?;
+    terminal
+        .start(&app, profile, cols, rows)
+        .await
+        .map_err(|e| e.to_string())
+}
 
-    #[cfg(target_os = "windows")]
-    {
-        // On Windows, open cmd with a specific title
-        Command::new("cmd")
-            .args(["/c", "start", "Claude Login", "cmd", "/k", "claude"])
-            .spawn()
-            .map_err(|e| format!("Failed to open Command Prompt: {}", e))?;
+#[tauri::command]
+pub fn write_login_terminal(
+    terminal: tauri::State<'_, LoginTerminal>,
+    data: String,
+) -> Result<(), String> {
+    terminal.write(&data).map_err(|e| e.to_string())
+}
 
-        return Ok(());
-    }
+#[tauri::command]
+pub fn resize_login_terminal(
+    terminal: tauri::State<'_, LoginTerminal>,
+    cols: u16,
+    rows: u16,
+) -> Result<(), String> {
+    terminal.resize(cols, rows).map_err(|e| e.to_string())
+}
 
-    #[cfg(target_os = "linux")]
-    {
-        // On Linux, try common terminal emulators with title
-        let terminals = [
-            ("gnome-terminal", vec!["--title=Claude Login", "--", "claude"]),
-            ("konsole", vec!["--title", "Claude Login", "-e", "claude"]),
-            ("xfce4-terminal", vec!["--title=Claude Login", "-e", "claude"]),
-            ("xterm", vec!["-title", "Claude Login", "-e", "claude"]),
-        ];
-
-        for (term, args) in terminals {
-            if Command::new(term).args(&args).spawn().is_ok() {
-                return Ok(());
-            }
-        }
+#[tauri::command]
+pub fn stop_login_terminal(app: tauri::AppHandle, terminal: tauri::State<'_, LoginTerminal>) {
+    terminal.stop(&app, None);
+}
 
-        return Err("No supported terminal emulator found".to_string());
-    }
+/// Where `claude` is installed and which version, so onboarding can explain
+/// how to install or update it before trying to log in
+#[tauri::command]
+pub async fn detect_claude_cli() -> Result<ClaudeCliInfo, String> {
+    tauri::async_runtime::spawn_blocking(claude_cli::detect)
+        .await
+        .map_err(|e| e.to_string())
+}
 
-    #[allow(unreachable_code)]
-    Err("Unsupported platform".to_string())
+#[tauri::command]
+pub fn launch_claude_cli() -> Result<(), String> {
+    let template = settings_store::load_settings_or_default().terminal_command;
+    terminal_launcher::launch(template.as_deref()).map_err(|e| e.to_string())
 }
 
 #[tauri::command]
 pub fn close_claude_terminal() -> Result<(), String> {
-    #[cfg(target_os = "macos")]
-    {
-        // Close Terminal windows with "Claude Login" in the title
-        let script = r#"tell application "Terminal"
-            set windowsToClose to every window whose name contains "Claude Login"
-            repeat with w in windowsToClose
-                close w
-            end repeat
-        end tell"#;
-
-        Command::new("osascript")
-            .arg("-e")
-            .arg(script)
-            .output()
-            .map_err(|e| format!("Failed to close Terminal: {}", e))?;
-
-        return Ok(());
-    }
-
-    #[cfg(target_os = "windows")]
-    {
-        // On Windows, close the window by title
-        Command::new("taskkill")
-            .args(["/FI", "WINDOWTITLE eq Claude Login", "/T", "/F"])
-            .output()
-            .ok();
-
-        return Ok(());
-    }
+    terminal_launcher::close();
+    Ok(())
+}
 
-    #[cfg(target_os = "linux")]
-    {
-        // On Linux, use wmctrl if available
-        Command::new("wmctrl")
-            .args(["-c", "Claude Login"])
-            .output()
-            .ok();
+/// Save the external terminal template, e.g. `kitty --title {title} -e {cmd}`
+#[tauri::command]
+pub fn save_terminal_command_setting(terminal_command: Option<String>) -> Result<(), String> {
+    let terminal_command = terminal_command
+        .map(|template| template.trim().to_string())
+        .filter(|template| !template.is_empty());
 
-        return Ok(());
+    if let Some(template) = &terminal_command {
+        terminal_launcher::parse_template(template, terminal_launcher::WINDOW_TITLE, "claude")
+            .map_err(|e| e.to_string())?;
     }
 
-    #[allow(unreachable_code)]
-    Ok(())
-}
-
-fn format_shortcut_display(config: &ShortcutConfig) -> String {
-    let modifier_display = match config.modifier.to_lowercase().as_str() {
-        "super" | "cmd" | "meta" => "Cmd",
-        "ctrl" | "control" => "Ctrl",
-        "alt" => "Alt",
-        "shift" => "Shift",
-        "ctrl+shift" | "control+shift" => "Ctrl+Shift",
-        "super+shift" | "cmd+shift" => "Cmd+Shift",
-        "alt+shift" => "Alt+Shift",
-        _ => &config.modifier,
-    };
-    format!("{}+{}", modifier_display, config.key.to_uppercase())
+    let mut settings = settings_store::load_settings().map_err(|e| e.to_string())?;
+    settings.terminal_command = terminal_command;
+    settings_store::save_settings(&settings).map_err(|e| e.to_string())
 }
@@ -1,43 +1,101 @@
-mod commands;
-mod models;
+pub mod commands;
+pub mod models;
 mod platform;
-mod services;
+pub mod services;
 
 use commands::{
     center_settings_window, check_credentials, close_claude_terminal, complete_first_launch,
-    fetch_usage_data, get_credentials_path_cmd, get_platform_info, get_settings, launch_claude_cli,
-    save_layout_setting, save_shortcut_setting, set_window_size,
+    detect_claude_cli, fetch_usage_data, fix_credentials_permissions, get_credentials_path_cmd,
+    get_platform_info, get_profiles, get_settings, launch_claude_cli, resize_login_terminal,
+    save_api_base_url_setting, save_credentials_path_setting, save_expiry_warning_setting,
+    save_layout_setting, save_network_setting, save_profiles, save_shortcut_setting,
+    save_terminal_command_setting, set_active_profile, set_window_size, start_login_terminal,
+    start_oauth_login, stop_login_terminal, take_missing_dirs, take_settings_recovery,
+    test_connection, write_login_terminal,
 };
-use services::settings_store;
-use tauri::{Emitter, Manager};
-use tauri_plugin_global_shortcut::{GlobalShortcutExt, ShortcutState};
+use services::login_terminal::LoginTerminal;
+use services::settings_store::SettingsRecovery;
+use services::shortcut_manager::ShortcutManager;
+use services::usage_scheduler::{self, UsageState};
+use services::{app_dirs, auth_watcher, diagnostics, expiry_monitor, settings_store};
+use tauri::Manager;
+use tauri_plugin_log::{Target, TargetKind};
+use tauri_plugin_notification::NotificationExt;
+
+fn notify_settings_recovered(app: &tauri::AppHandle, recovery: &SettingsRecovery) {
+    let body = if recovery.restored_from_backup {
+        "Settings were corrupt and have been restored from the last backup."
+    } else {
+        "Settings were corrupt and have been reset to defaults."
+    };
+    let _ = app
+        .notification()
+        .builder()
+        .title("Claude Usage Monitor settings recovered")
+        .body(format!(
+            "{} The damaged file was kept at {}",
+            body, recovery.quarantined_path
+        ))
+        .show();
+}
+
+/// Nothing can be saved without a home directory, and the diagnostics log
+/// can't be written either, so it goes to the log and a notification. The
+/// frontend shows it too, through `take_missing_dirs`.
+fn notify_no_app_dirs(app: &tauri::AppHandle, error: &app_dirs::NoHomeDir) {
+    log::error!("{}", error);
+    let _ = app
+        .notification()
+        .builder()
+        .title("Claude Usage Monitor can't save settings")
+        .body(error.to_string())
+        .show();
+}
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
+    let missing_dirs = app_dirs::check_available().err();
+
+    // Before anything reads settings, so nothing sees the old location or a
+    // corrupt file
+    if let Err(e) = app_dirs::migrate_legacy_dir() {
+        diagnostics::record_error("settings", &format!("moving files failed: {}", e));
+    }
+    let settings_recovery = settings_store::recover_corrupt_settings();
+    if let Err(e) = settings_store::upgrade_settings_file() {
+        diagnostics::record_error("settings", &format!("upgrade failed: {}", e));
+    }
+
     tauri::Builder::default()
         .plugin(tauri_plugin_shell::init())
         .plugin(tauri_plugin_global_shortcut::Builder::new().build())
-        .setup(|app| {
-            let settings = settings_store::load_settings();
-
-            if let Some(shortcut) = platform::parse_shortcut(&settings.shortcut) {
-                let app_handle = app.handle().clone();
+        .plugin(tauri_plugin_notification::init())
+        // Stderr only: a log folder may be exactly what's missing
+        .plugin(
+            tauri_plugin_log::Builder::new()
+                .clear_targets()
+                .target(Target::new(TargetKind::Stderr))
+                .level(log::LevelFilter::Info)
+                .build(),
+        )
+        .manage(UsageState::load())
+        .manage(LoginTerminal::default())
+        .manage(ShortcutManager::default())
+        .setup(move |app| {
+            if let Some(error) = &missing_dirs {
+                notify_no_app_dirs(app.handle(), error);
+            }
+            if let Some(recovery) = &settings_recovery {
+                notify_settings_recovered(app.handle(), recovery);
+            }
+            let settings = settings_store::load_settings_or_default();
 
-                app.global_shortcut()
-                    .on_shortcut(shortcut, move |_app, _shortcut, event| {
-                        if event.state() != ShortcutState::Pressed {
-                            return;
-                        }
-                        if let Some(window) = app_handle.get_webview_window("main") {
-                            if window.is_visible().unwrap_or(false) {
-                                let _ = window.hide();
-                                let _ = window.emit("overlay-visibility", false);
-                            } else {
-                                let _ = window.show();
-                                let _ = window.emit("overlay-visibility", true);
-                            }
-                        }
-                    })?;
+            // A taken or invalid shortcut shouldn't keep the app from starting
+            if let Err(e) = app
+                .state::<ShortcutManager>()
+                .apply(app.handle(), &settings.shortcut)
+            {
+                diagnostics::record_error("shortcut", &e.to_string());
             }
 
             // Platform-specific window positioning (skip on first launch - will be centered by frontend)
@@ -70,21 +128,43 @@ pub fn run() {
                 }
             }
 
+            usage_scheduler::start(app.handle().clone());
+            auth_watcher::start(app.handle().clone());
+            expiry_monitor::start(app.handle().clone());
+
             Ok(())
         })
         .invoke_handler(tauri::generate_handler![
             check_credentials,
+            fix_credentials_permissions,
+            start_oauth_login,
             fetch_usage_data,
             get_credentials_path_cmd,
             get_settings,
             save_shortcut_setting,
             save_layout_setting,
+            save_api_base_url_setting,
+            save_credentials_path_setting,
+            save_network_setting,
+            save_expiry_warning_setting,
+            test_connection,
+            get_profiles,
+            save_profiles,
+            set_active_profile,
+            save_terminal_command_setting,
             complete_first_launch,
             get_platform_info,
             center_settings_window,
             set_window_size,
+            detect_claude_cli,
             launch_claude_cli,
-            close_claude_terminal
+            close_claude_terminal,
+            take_settings_recovery,
+            take_missing_dirs,
+            start_login_terminal,
+            write_login_terminal,
+            resize_login_terminal,
+            stop_login_terminal
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
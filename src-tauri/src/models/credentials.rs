@@ -1,31 +1,72 @@
 use serde::{Deserialize, Serialize};
 
+use super::Secret;
+
 #[derive(Debug, Deserialize)]
 pub struct CredentialsFile {
     #[serde(rename = "claudeAiOauth")]
     pub claude_ai_oauth: Option<ClaudeOAuth>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, Deserialize)]
 pub struct ClaudeOAuth {
     #[serde(rename = "accessToken")]
-    pub access_token: String,
+    pub access_token: Secret,
 
-    #[allow(dead_code)]
     #[serde(rename = "refreshToken")]
-    pub refresh_token: Option<String>,
+    pub refresh_token: Option<Secret>,
 
     #[serde(rename = "expiresAt")]
     pub expires_at: i64,
 
-    #[allow(dead_code)]
     pub scopes: Option<Vec<String>>,
 }
 
-#[derive(Debug, Serialize)]
+/// Response body of the OAuth `refresh_token` grant
+#[derive(Debug, Deserialize)]
+pub struct OAuthTokenResponse {
+    pub access_token: Secret,
+    pub refresh_token: Option<Secret>,
+    pub expires_in: i64,
+    pub scope: Option<String>,
+}
+
+/// Why a credential provider did not supply the token
+#[derive(Debug, Clone, Serialize)]
+pub struct ProviderAttempt {
+    pub provider: String,
+    pub location: String,
+    pub reason: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
 pub struct AuthStatus {
+    /// Profile these credentials belong to
+    pub profile_id: String,
     pub authenticated: bool,
     pub expires_at: Option<i64>,
     pub credentials_path: String,
     pub error_reason: Option<String>,
+    /// Provider that supplied the token (`env`, `file`, `default_file`, `keychain`, `process`)
+    pub source: Option<String>,
+    /// Providers tried before `source`, or all of them when none succeeded
+    pub skipped_providers: Vec<ProviderAttempt>,
+    /// Set when the credentials file is readable by other users
+    pub permission_warning: Option<FilePermissionWarning>,
+    /// How far the API server's clock is ahead of ours, when large enough to matter
+    pub clock_skew_seconds: Option<i64>,
+    /// Scopes the usage endpoint needs that the token lacks
+    pub missing_scopes: Vec<String>,
+}
+
+/// Unsafe mode or owner on a credentials file
+#[derive(Debug, Clone, Serialize)]
+pub struct FilePermissionWarning {
+    pub path: String,
+    /// Permission bits in octal, e.g. `0644`
+    pub mode: String,
+    /// `group_readable`, `world_readable` or `wrong_owner`
+    pub reason: String,
+    /// Whether tightening the mode to 0600 resolves it
+    pub fixable: bool,
 }
@@ -1,5 +1,7 @@
 mod credentials;
+mod secret;
 mod usage;
 
 pub use credentials::*;
+pub use secret::*;
 pub use usage::*;
@@ -1,11 +1,34 @@
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Serialize, Deserialize)]
+/// Buckets the app knows about: API key, display label, overlay label
+const KNOWN_BUCKETS: &[(&str, &str, &str)] = &[
+    ("five_hour", "5-Hour", "5H"),
+    ("seven_day", "7-Day", "7D"),
+    ("seven_day_sonnet", "Sonnet", "SNT"),
+    ("seven_day_opus", "Opus", "OPS"),
+];
+
+/// Buckets the API has always returned; their absence is schema drift
+const REQUIRED_BUCKETS: &[&str] = &["five_hour", "seven_day"];
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct UsageResponse {
-    pub five_hour: UsageMetric,
-    pub seven_day: UsageMetric,
+    pub five_hour: Option<UsageMetric>,
+    pub seven_day: Option<UsageMetric>,
     pub seven_day_opus: Option<UsageMetric>,
     pub seven_day_sonnet: Option<UsageMetric>,
+    /// Every metric-shaped bucket in API order, including ones this version doesn't know
+    #[serde(default)]
+    pub buckets: Vec<UsageBucket>,
+    /// When this data was fetched from the API (Unix ms)
+    #[serde(default)]
+    pub fetched_at: Option<i64>,
+    /// Set while fetches are failing and this is the last known data (Unix ms)
+    #[serde(default)]
+    pub stale_since: Option<i64>,
+    /// Differences between the API response and the expected schema
+    #[serde(skip)]
+    pub schema_drift: Vec<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -13,3 +36,121 @@ pub struct UsageMetric {
     pub utilization: f64,
     pub resets_at: Option<String>,
 }
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct UsageBucket {
+    pub key: String,
+    pub label: String,
+    pub short_label: String,
+    pub known: bool,
+    pub metric: UsageMetric,
+}
+
+impl UsageBucket {
+    fn new(key: &str, metric: UsageMetric) -> Self {
+        match KNOWN_BUCKETS.iter().find(|(known, _, _)| *known == key) {
+            Some((_, label, short_label)) => Self {
+                key: key.to_string(),
+                label: label.to_string(),
+                short_label: short_label.to_string(),
+                known: true,
+                metric,
+            },
+            None => Self {
+                key: key.to_string(),
+                label: humanize_key(key),
+                short_label: abbreviate_key(key),
+                known: false,
+                metric,
+            },
+        }
+    }
+}
+
+/// `seven_day_oauth_apps` -> `Seven Day Oauth Apps`
+fn humanize_key(key: &str) -> String {
+    key.split('_')
+        .filter(|word| !word.is_empty())
+        .map(|word| {
+            let mut chars = word.chars();
+            match chars.next() {
+                Some(first) => first.to_uppercase().chain(chars).collect::<String>(),
+                None => String::new(),
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// `seven_day_oauth_apps` -> `SDOA`
+fn abbreviate_key(key: &str) -> String {
+    key.split('_')
+        .filter_map(|word| word.chars().next())
+        .take(4)
+        .collect::<String>()
+        .to_uppercase()
+}
+
+impl UsageResponse {
+    /// Build from the raw API body without failing on added, removed or
+    /// reshaped fields; anything unexpected is recorded in `schema_drift`
+    pub fn from_api_value(value: serde_json::Value) -> Result<Self, String> {
+        let serde_json::Value::Object(fields) = value else {
+            return Err("usage response is not a JSON object".to_string());
+        };
+
+        let mut usage = Self {
+            five_hour: None,
+            seven_day: None,
+            seven_day_opus: None,
+            seven_day_sonnet: None,
+            buckets: Vec::new(),
+            fetched_at: None,
+            stale_since: None,
+            schema_drift: Vec::new(),
+        };
+
+        for (key, value) in fields {
+            let known = KNOWN_BUCKETS.iter().any(|(known, _, _)| *known == key);
+            if value.is_null() {
+                if !known {
+                    usage
+                        .schema_drift
+                        .push(format!("unknown field `{}` is null", key));
+                }
+                continue;
+            }
+
+            match serde_json::from_value::<UsageMetric>(value) {
+                Ok(metric) => {
+                    match key.as_str() {
+                        "five_hour" => usage.five_hour = Some(metric.clone()),
+                        "seven_day" => usage.seven_day = Some(metric.clone()),
+                        "seven_day_opus" => usage.seven_day_opus = Some(metric.clone()),
+                        "seven_day_sonnet" => usage.seven_day_sonnet = Some(metric.clone()),
+                        _ => usage
+                            .schema_drift
+                            .push(format!("unknown usage bucket `{}`", key)),
+                    }
+                    usage.buckets.push(UsageBucket::new(&key, metric));
+                }
+                Err(e) if known => usage
+                    .schema_drift
+                    .push(format!("field `{}` has unexpected shape: {}", key, e)),
+                Err(_) => usage
+                    .schema_drift
+                    .push(format!("unknown field `{}` is not a usage bucket", key)),
+            }
+        }
+
+        for required in REQUIRED_BUCKETS {
+            if !usage.buckets.iter().any(|bucket| bucket.key == *required) {
+                usage
+                    .schema_drift
+                    .push(format!("missing field `{}`", required));
+            }
+        }
+
+        Ok(usage)
+    }
+}
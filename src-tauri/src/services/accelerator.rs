@@ -0,0 +1,513 @@
+//! Shortcut grammar: the `modifier`/`key` pair stored in settings and the
+//! label shown to the user, both parsed back to the same `Shortcut`
+
+use tauri_plugin_global_shortcut::{Code, Modifiers, Shortcut};
+use thiserror::Error;
+
+use crate::services::settings_store::ShortcutConfig;
+
+/// Every key a shortcut can use, in `keyboard-types` order. `Unidentified`
+/// is left out since it matches no physical key.
+pub const ALL_CODES: &[Code] = &[
+    Code::Backquote,
+    Code::Backslash,
+    Code::BracketLeft,
+    Code::BracketRight,
+    Code::Comma,
+    Code::Digit0,
+    Code::Digit1,
+    Code::Digit2,
+    Code::Digit3,
+    Code::Digit4,
+    Code::Digit5,
+    Code::Digit6,
+    Code::Digit7,
+    Code::Digit8,
+    Code::Digit9,
+    Code::Equal,
+    Code::IntlBackslash,
+    Code::IntlRo,
+    Code::IntlYen,
+    Code::KeyA,
+    Code::KeyB,
+    Code::KeyC,
+    Code::KeyD,
+    Code::KeyE,
+    Code::KeyF,
+    Code::KeyG,
+    Code::KeyH,
+    Code::KeyI,
+    Code::KeyJ,
+    Code::KeyK,
+    Code::KeyL,
+    Code::KeyM,
+    Code::KeyN,
+    Code::KeyO,
+    Code::KeyP,
+    Code::KeyQ,
+    Code::KeyR,
+    Code::KeyS,
+    Code::KeyT,
+    Code::KeyU,
+    Code::KeyV,
+    Code::KeyW,
+    Code::KeyX,
+    Code::KeyY,
+    Code::KeyZ,
+    Code::Minus,
+    Code::Period,
+    Code::Quote,
+    Code::Semicolon,
+    Code::Slash,
+    Code::AltLeft,
+    Code::AltRight,
+    Code::Backspace,
+    Code::CapsLock,
+    Code::ContextMenu,
+    Code::ControlLeft,
+    Code::ControlRight,
+    Code::Enter,
+    Code::MetaLeft,
+    Code::MetaRight,
+    Code::ShiftLeft,
+    Code::ShiftRight,
+    Code::Space,
+    Code::Tab,
+    Code::Convert,
+    Code::KanaMode,
+    Code::Lang1,
+    Code::Lang2,
+    Code::Lang3,
+    Code::Lang4,
+    Code::Lang5,
+    Code::NonConvert,
+    Code::Delete,
+    Code::End,
+    Code::Help,
+    Code::Home,
+    Code::Insert,
+    Code::PageDown,
+    Code::PageUp,
+    Code::ArrowDown,
+    Code::ArrowLeft,
+    Code::ArrowRight,
+    Code::ArrowUp,
+    Code::NumLock,
+    Code::Numpad0,
+    Code::Numpad1,
+    Code::Numpad2,
+    Code::Numpad3,
+    Code::Numpad4,
+    Code::Numpad5,
+    Code::Numpad6,
+    Code::Numpad7,
+    Code::Numpad8,
+    Code::Numpad9,
+    Code::NumpadAdd,
+    Code::NumpadBackspace,
+    Code::NumpadClear,
+    Code::NumpadClearEntry,
+    Code::NumpadComma,
+    Code::NumpadDecimal,
+    Code::NumpadDivide,
+    Code::NumpadEnter,
+    Code::NumpadEqual,
+    Code::NumpadHash,
+    Code::NumpadMemoryAdd,
+    Code::NumpadMemoryClear,
+    Code::NumpadMemoryRecall,
+    Code::NumpadMemoryStore,
+    Code::NumpadMemorySubtract,
+    Code::NumpadMultiply,
+    Code::NumpadParenLeft,
+    Code::NumpadParenRight,
+    Code::NumpadStar,
+    Code::NumpadSubtract,
+    Code::Escape,
+    Code::Fn,
+    Code::FnLock,
+    Code::PrintScreen,
+    Code::ScrollLock,
+    Code::Pause,
+    Code::BrowserBack,
+    Code::BrowserFavorites,
+    Code::BrowserForward,
+    Code::BrowserHome,
+    Code::BrowserRefresh,
+    Code::BrowserSearch,
+    Code::BrowserStop,
+    Code::Eject,
+    Code::LaunchApp1,
+    Code::LaunchApp2,
+    Code::LaunchMail,
+    Code::MediaPlayPause,
+    Code::MediaSelect,
+    Code::MediaStop,
+    Code::MediaTrackNext,
+    Code::MediaTrackPrevious,
+    Code::Power,
+    Code::Sleep,
+    Code::AudioVolumeDown,
+    Code::AudioVolumeMute,
+    Code::AudioVolumeUp,
+    Code::WakeUp,
+    Code::Hyper,
+    Code::Super,
+    Code::Turbo,
+    Code::Abort,
+    Code::Resume,
+    Code::Suspend,
+    Code::Again,
+    Code::Copy,
+    Code::Cut,
+    Code::Find,
+    Code::Open,
+    Code::Paste,
+    Code::Props,
+    Code::Select,
+    Code::Undo,
+    Code::Hiragana,
+    Code::Katakana,
+    Code::F1,
+    Code::F2,
+    Code::F3,
+    Code::F4,
+    Code::F5,
+    Code::F6,
+    Code::F7,
+    Code::F8,
+    Code::F9,
+    Code::F10,
+    Code::F11,
+    Code::F12,
+    Code::F13,
+    Code::F14,
+    Code::F15,
+    Code::F16,
+    Code::F17,
+    Code::F18,
+    Code::F19,
+    Code::F20,
+    Code::F21,
+    Code::F22,
+    Code::F23,
+    Code::F24,
+    Code::F25,
+    Code::F26,
+    Code::F27,
+    Code::F28,
+    Code::F29,
+    Code::F30,
+    Code::F31,
+    Code::F32,
+    Code::F33,
+    Code::F34,
+    Code::F35,
+    Code::BrightnessDown,
+    Code::BrightnessUp,
+    Code::DisplayToggleIntExt,
+    Code::KeyboardLayoutSelect,
+    Code::LaunchAssistant,
+    Code::LaunchControlPanel,
+    Code::LaunchScreenSaver,
+    Code::MailForward,
+    Code::MailReply,
+    Code::MailSend,
+    Code::MediaFastForward,
+    Code::MediaPause,
+    Code::MediaPlay,
+    Code::MediaRecord,
+    Code::MediaRewind,
+    Code::MicrophoneMuteToggle,
+    Code::PrivacyScreenToggle,
+    Code::SelectTask,
+    Code::ShowAllWindows,
+    Code::ZoomToggle,
+];
+
+/// The modifiers a shortcut can combine, in the order settings store them
+pub const ALL_MODIFIERS: &[Modifiers] = &[
+    Modifiers::SUPER,
+    Modifiers::CONTROL,
+    Modifiers::ALT,
+    Modifiers::SHIFT,
+];
+
+/// Keys labelled by the character they type
+const SYMBOL_LABELS: &[(Code, &str)] = &[
+    (Code::Backquote, "`"),
+    (Code::Backslash, "\\"),
+    (Code::BracketLeft, "["),
+    (Code::BracketRight, "]"),
+    (Code::Comma, ","),
+    (Code::Equal, "="),
+    (Code::Minus, "-"),
+    (Code::Period, "."),
+    (Code::Quote, "'"),
+    (Code::Semicolon, ";"),
+    (Code::Slash, "/"),
+];
+
+/// Keys macOS menus draw as a symbol
+const GLYPH_LABELS: &[(Code, &str)] = &[
+    (Code::ArrowUp, "↑"),
+    (Code::ArrowDown, "↓"),
+    (Code::ArrowLeft, "←"),
+    (Code::ArrowRight, "→"),
+    (Code::Enter, "↩"),
+    (Code::Escape, "⎋"),
+    (Code::Backspace, "⌫"),
+    (Code::Delete, "⌦"),
+    (Code::Tab, "⇥"),
+    (Code::PageUp, "⇞"),
+    (Code::PageDown, "⇟"),
+    (Code::Home, "↖"),
+    (Code::End, "↘"),
+];
+
+/// Short names people type for keys whose `Code` name is longer
+const KEY_ALIASES: &[(&str, Code)] = &[
+    ("esc", Code::Escape),
+    ("return", Code::Enter),
+    ("spacebar", Code::Space),
+    ("up", Code::ArrowUp),
+    ("down", Code::ArrowDown),
+    ("left", Code::ArrowLeft),
+    ("right", Code::ArrowRight),
+    ("pgup", Code::PageUp),
+    ("pgdn", Code::PageDown),
+    ("del", Code::Delete),
+    ("ins", Code::Insert),
+];
+
+/// macOS modifier symbols, in the order menus draw them
+const MODIFIER_GLYPHS: &[(Modifiers, char)] = &[
+    (Modifiers::CONTROL, '⌃'),
+    (Modifiers::ALT, '⌥'),
+    (Modifiers::SHIFT, '⇧'),
+    (Modifiers::SUPER, '⌘'),
+];
+
+#[derive(Error, Debug, Clone, PartialEq)]
+pub enum AcceleratorError {
+    #[error("Unknown modifier \"{0}\"")]
+    UnknownModifier(String),
+
+    #[error("Unknown key \"{0}\"")]
+    UnknownKey(String),
+
+    #[error("The shortcut has no key")]
+    MissingKey,
+}
+
+/// How shortcuts are labelled for the user
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DisplayStyle {
+    /// `⌃⌥⇧⌘K`, as in macOS menus
+    Glyphs,
+    /// `Win+Ctrl+Alt+Shift+K`
+    Windows,
+    /// `Super+Ctrl+Alt+Shift+K`
+    Text,
+}
+
+impl DisplayStyle {
+    pub fn native() -> Self {
+        if cfg!(target_os = "macos") {
+            DisplayStyle::Glyphs
+        } else if cfg!(target_os = "windows") {
+            DisplayStyle::Windows
+        } else {
+            DisplayStyle::Text
+        }
+    }
+}
+
+fn parse_modifier(name: &str) -> Result<Modifiers, AcceleratorError> {
+    match name.to_lowercase().as_str() {
+        "super" | "cmd" | "command" | "meta" | "win" | "windows" | "⌘" => Ok(Modifiers::SUPER),
+        "ctrl" | "control" | "ctl" | "⌃" => Ok(Modifiers::CONTROL),
+        "alt" | "option" | "opt" | "⌥" => Ok(Modifiers::ALT),
+        "shift" | "⇧" => Ok(Modifiers::SHIFT),
+        _ => Err(AcceleratorError::UnknownModifier(name.to_string())),
+    }
+}
+
+/// `ctrl+alt+shift`, in any case and order with the usual aliases. An empty
+/// string is no modifiers at all.
+pub fn parse_modifiers(modifiers: &str) -> Result<Modifiers, AcceleratorError> {
+    modifiers
+        .split('+')
+        .map(str::trim)
+        .filter(|name| !name.is_empty())
+        .try_fold(Modifiers::empty(), |all, name| {
+            Ok(all | parse_modifier(name)?)
+        })
+}
+
+/// A key by its `Code` name (`F5`, `ArrowUp`, `NumpadAdd`, any case), the
+/// character it types (`k`, `7`, `/`), a macOS glyph or a common alias
+pub fn parse_key(key: &str) -> Result<Code, AcceleratorError> {
+    let key = key.trim();
+    if key.is_empty() {
+        return Err(AcceleratorError::MissingKey);
+    }
+
+    let mut chars = key.chars();
+    if let (Some(c), None) = (chars.next(), chars.next()) {
+        if c.is_ascii_alphabetic() {
+            return key_code(&format!("Key{}", c.to_ascii_uppercase()));
+        }
+        if c.is_ascii_digit() {
+            return key_code(&format!("Digit{}", c));
+        }
+    }
+
+    SYMBOL_LABELS
+        .iter()
+        .chain(GLYPH_LABELS)
+        .find(|(_, label)| *label == key)
+        .map(|(code, _)| *code)
+        .or_else(|| {
+            ALL_CODES
+                .iter()
+                .find(|code| code.to_string().eq_ignore_ascii_case(key))
+                .copied()
+        })
+        .or_else(|| {
+            KEY_ALIASES
+                .iter()
+                .find(|(alias, _)| alias.eq_ignore_ascii_case(key))
+                .map(|(_, code)| *code)
+        })
+        .ok_or_else(|| AcceleratorError::UnknownKey(key.to_string()))
+}
+
+fn key_code(name: &str) -> Result<Code, AcceleratorError> {
+    name.parse()
+        .map_err(|_| AcceleratorError::UnknownKey(name.to_string()))
+}
+
+/// The shortcut a settings entry describes
+pub fn parse_config(config: &ShortcutConfig) -> Result<Shortcut, AcceleratorError> {
+    Ok(Shortcut::new(
+        Some(parse_modifiers(&config.modifier)?),
+        parse_key(&config.key)?,
+    ))
+}
+
+/// Canonical settings entry: `super+ctrl+alt+shift` order, letters and
+/// digits as the character, other keys by `Code` name
+pub fn to_config(shortcut: &Shortcut) -> ShortcutConfig {
+    let modifier = ALL_MODIFIERS
+        .iter()
+        .filter(|m| shortcut.mods.contains(**m))
+        .map(|m| match *m {
+            Modifiers::SUPER => "super",
+            Modifiers::CONTROL => "ctrl",
+            Modifiers::ALT => "alt",
+            _ => "shift",
+        })
+        .collect::<Vec<_>>()
+        .join("+");
+
+    let name = shortcut.key.to_string();
+    let key = match name
+        .strip_prefix("Key")
+        .or_else(|| name.strip_prefix("Digit"))
+    {
+        Some(c) if c.len() == 1 => c.to_lowercase(),
+        _ => name,
+    };
+    ShortcutConfig { modifier, key }
+}
+
+fn key_label(code: Code, style: DisplayStyle) -> String {
+    let glyphs = if style == DisplayStyle::Glyphs {
+        GLYPH_LABELS
+    } else {
+        &[]
+    };
+    if let Some((_, label)) = SYMBOL_LABELS
+        .iter()
+        .chain(glyphs)
+        .find(|(known, _)| *known == code)
+    {
+        return label.to_string();
+    }
+
+    let name = code.to_string();
+    match name
+        .strip_prefix("Key")
+        .or_else(|| name.strip_prefix("Digit"))
+    {
+        Some(c) if c.len() == 1 => c.to_string(),
+        _ => name,
+    }
+}
+
+/// Label for the user, e.g. `⇧⌘U` or `Ctrl+Shift+U`
+pub fn format_shortcut(shortcut: &Shortcut, style: DisplayStyle) -> String {
+    let key = key_label(shortcut.key, style);
+    if style == DisplayStyle::Glyphs {
+        let glyphs: String = MODIFIER_GLYPHS
+            .iter()
+            .filter(|(m, _)| shortcut.mods.contains(*m))
+            .map(|(_, glyph)| glyph)
+            .collect();
+        return glyphs + &key;
+    }
+
+    let super_name = if style == DisplayStyle::Windows {
+        "Win"
+    } else {
+        "Super"
+    };
+    let names = [
+        (Modifiers::SUPER, super_name),
+        (Modifiers::CONTROL, "Ctrl"),
+        (Modifiers::ALT, "Alt"),
+        (Modifiers::SHIFT, "Shift"),
+    ];
+    names
+        .iter()
+        .filter(|(m, _)| shortcut.mods.contains(*m))
+        .map(|(_, name)| *name)
+        .chain(std::iter::once(key.as_str()))
+        .collect::<Vec<_>>()
+        .join("+")
+}
+
+/// Read back a label from `format_shortcut` in any style: leading modifier
+/// glyphs, then `+`-separated modifier names ending with the key
+pub fn parse_display(label: &str) -> Result<Shortcut, AcceleratorError> {
+    let label = label.trim();
+    let mut mods = Modifiers::empty();
+    let mut rest = label;
+    while let Some(c) = rest.chars().next() {
+        let Some((m, _)) = MODIFIER_GLYPHS.iter().find(|(_, glyph)| *glyph == c) else {
+            break;
+        };
+        mods |= *m;
+        rest = &rest[c.len_utf8()..];
+    }
+
+    let (names, key) = match rest.rsplit_once('+') {
+        Some((names, key)) => (names, key),
+        None => ("", rest),
+    };
+    Ok(Shortcut::new(
+        Some(mods | parse_modifiers(names)?),
+        parse_key(key)?,
+    ))
+}
+
+/// Label for a settings entry in the platform's style; entries that don't
+/// parse are shown as stored so the user can see what is wrong
+pub fn format_shortcut_display(config: &ShortcutConfig) -> String {
+    match parse_config(config) {
+        Ok(shortcut) => format_shortcut(&shortcut, DisplayStyle::native()),
+        Err(_) if config.modifier.is_empty() => config.key.clone(),
+        Err(_) => format!("{}+{}", config.modifier, config.key),
+    }
+}
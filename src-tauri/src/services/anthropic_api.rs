@@ -1,41 +1,367 @@
+use std::sync::{Arc, Mutex, PoisonError};
+use std::time::Duration;
+
+use reqwest::header::{HeaderMap, RETRY_AFTER};
 use thiserror::Error;
 
-use crate::models::UsageResponse;
+use crate::models::{OAuthTokenResponse, Secret, UsageResponse};
+use crate::services::settings_store::{self, NetworkConfig};
+use crate::services::{clock, diagnostics, network, redact};
 
-const USAGE_ENDPOINT: &str = "https://api.anthropic.com/api/oauth/usage";
+const DEFAULT_API_BASE_URL: &str = "https://api.anthropic.com";
+/// Overrides the API base URL from settings, e.g. to run against a local mock server
+const API_BASE_URL_ENV: &str = "CLAUDE_USAGE_MONITOR_API_BASE_URL";
+const USAGE_PATH: &str = "/api/oauth/usage";
+/// OAuth scopes the usage endpoint requires; without them it answers 403
+pub const USAGE_REQUIRED_SCOPES: &[&str] = &["user:profile"];
+const TOKEN_ENDPOINT: &str = "https://console.anthropic.com/v1/oauth/token";
+/// Overrides `TOKEN_ENDPOINT`, e.g. to point the refresh flow at a local mock server
+const TOKEN_ENDPOINT_ENV: &str = "CLAUDE_USAGE_MONITOR_TOKEN_ENDPOINT";
+const AUTHORIZE_ENDPOINT: &str = "https://claude.ai/oauth/authorize";
+/// Overrides `AUTHORIZE_ENDPOINT` for the in-app login
+const AUTHORIZE_ENDPOINT_ENV: &str = "CLAUDE_USAGE_MONITOR_AUTHORIZE_ENDPOINT";
+/// Public OAuth client id used by the Claude Code CLI
+pub(crate) const OAUTH_CLIENT_ID: &str = "9d1c250a-e61b-44d9-88ed-5944d1962f5e";
 const USER_AGENT: &str = "claude-code-usage-monitor/1.0.0";
 const ANTHROPIC_BETA: &str = "oauth-2025-04-20";
 
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
+const READ_TIMEOUT: Duration = Duration::from_secs(30);
+const MAX_RETRIES: u32 = 3;
+const BASE_BACKOFF: Duration = Duration::from_millis(500);
+const MAX_BACKOFF: Duration = Duration::from_secs(8);
+/// Overrides `BASE_BACKOFF` in milliseconds, e.g. so tests retry without waiting
+const BACKOFF_ENV: &str = "CLAUDE_USAGE_MONITOR_RETRY_BACKOFF_MS";
+
 #[derive(Error, Debug)]
 pub enum ApiError {
     #[error("Network error: {0}")]
     Network(#[from] reqwest::Error),
 
+    #[error("Network configuration error: {0}")]
+    Config(String),
+
+    #[error("Rate limited{}", format_retry_after(.retry_after))]
+    RateLimited { retry_after: Option<u64> },
+
+    #[error("Unauthorized. Please login again using 'claude' CLI.")]
+    Unauthorized,
+
+    #[error("Server error: {status}")]
+    Server { status: u16, message: String },
+
+    #[error("Failed to decode response: {0}")]
+    Decode(String),
+
     #[error("API returned error: {status} - {message}")]
     Response { status: u16, message: String },
 }
 
-pub async fn fetch_usage(access_token: &str) -> Result<UsageResponse, ApiError> {
-    let client = reqwest::Client::new();
+fn format_retry_after(retry_after: &Option<u64>) -> String {
+    match retry_after {
+        Some(secs) => format!(", retrying in {}s", secs),
+        None => String::new(),
+    }
+}
+
+impl ApiError {
+    /// Transient failures worth retrying within the same fetch
+    fn is_retryable(&self) -> bool {
+        match self {
+            Self::Network(e) => e.is_timeout() || e.is_connect() || e.is_request(),
+            Self::Server { .. } => true,
+            _ => false,
+        }
+    }
+
+    /// The request failed before reaching the server, so sending it again
+    /// can't repeat anything the server did
+    fn is_connect_failure(&self) -> bool {
+        matches!(self, Self::Network(e) if e.is_connect())
+    }
+
+    /// Seconds the server asked us to wait before the next request
+    pub fn retry_after(&self) -> Option<u64> {
+        match self {
+            Self::RateLimited { retry_after } => *retry_after,
+            _ => None,
+        }
+    }
+
+    /// Short machine-readable identifier for the frontend
+    pub fn code(&self) -> &'static str {
+        match self {
+            Self::Network(_) => "network",
+            Self::Config(_) => "config",
+            Self::RateLimited { .. } => "rate_limited",
+            Self::Unauthorized => "unauthorized",
+            Self::Server { .. } => "server",
+            Self::Decode(_) => "decode",
+            Self::Response { .. } => "response",
+        }
+    }
+}
 
-    let response = client
-        .get(USAGE_ENDPOINT)
-        .header("Authorization", format!("Bearer {}", access_token))
-        .header("User-Agent", USER_AGENT)
-        .header("anthropic-beta", ANTHROPIC_BETA)
-        .send()
-        .await?;
+/// Parse `Retry-After` as either delta-seconds or an HTTP date
+fn parse_retry_after(headers: &HeaderMap) -> Option<u64> {
+    let value = headers.get(RETRY_AFTER)?.to_str().ok()?.trim();
 
+    if let Ok(secs) = value.parse::<u64>() {
+        return Some(secs);
+    }
+
+    let date = chrono::DateTime::parse_from_rfc2822(value).ok()?;
+    let secs = (date.timestamp() - chrono::Utc::now().timestamp()).max(0);
+    Some(secs as u64)
+}
+
+fn base_backoff() -> Duration {
+    std::env::var(BACKOFF_ENV)
+        .ok()
+        .and_then(|ms| ms.trim().parse().ok())
+        .map_or(BASE_BACKOFF, Duration::from_millis)
+}
+
+/// Exponential backoff with up to 50% random jitter
+fn backoff_delay(base: Duration, attempt: u32) -> Duration {
+    let exp = base.saturating_mul(1 << attempt.min(16)).min(MAX_BACKOFF);
+    let nanos = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.subsec_nanos() as u64)
+        .unwrap_or(0);
+    let jitter_ms = nanos % (exp.as_millis() as u64 / 2 + 1);
+    exp + Duration::from_millis(jitter_ms)
+}
+
+async fn error_from_response(response: reqwest::Response) -> ApiError {
     let status = response.status();
 
-    if !status.is_success() {
-        let message = response.text().await.unwrap_or_default();
-        return Err(ApiError::Response {
+    if status == reqwest::StatusCode::TOO_MANY_REQUESTS {
+        return ApiError::RateLimited {
+            retry_after: parse_retry_after(response.headers()),
+        };
+    }
+    if status == reqwest::StatusCode::UNAUTHORIZED {
+        return ApiError::Unauthorized;
+    }
+
+    // Bodies end up in the UI and in pasted bug reports
+    let message = redact::scrub_error_body(&response.text().await.unwrap_or_default());
+    if status.is_server_error() {
+        ApiError::Server {
+            status: status.as_u16(),
+            message,
+        }
+    } else {
+        ApiError::Response {
             status: status.as_u16(),
             message,
-        });
+        }
+    }
+}
+
+async fn decode<T: serde::de::DeserializeOwned>(
+    response: reqwest::Response,
+) -> Result<T, ApiError> {
+    let body = response.bytes().await?;
+    serde_json::from_slice(&body).map_err(|e| ApiError::Decode(e.to_string()))
+}
+
+/// HTTP client for the Anthropic OAuth endpoints, shared so connections are pooled
+pub struct UsageClient {
+    http: reqwest::Client,
+}
+
+impl UsageClient {
+    pub fn with_config(config: &NetworkConfig) -> Result<Self, ApiError> {
+        let builder = reqwest::Client::builder()
+            .user_agent(USER_AGENT)
+            .connect_timeout(CONNECT_TIMEOUT)
+            .read_timeout(READ_TIMEOUT);
+        let http = network::configure_client(builder, config)
+            .map_err(|e| ApiError::Config(e.to_string()))?
+            .build()?;
+        Ok(Self { http })
+    }
+
+    /// Client for the current network settings, rebuilt only when they change
+    pub fn shared() -> Result<Arc<UsageClient>, ApiError> {
+        static CLIENT: Mutex<Option<(NetworkConfig, Arc<UsageClient>)>> = Mutex::new(None);
+
+        let config = network::effective_network_config();
+        let mut cached = CLIENT.lock().unwrap_or_else(PoisonError::into_inner);
+        if let Some((built_for, client)) = cached.as_ref() {
+            if *built_for == config {
+                return Ok(client.clone());
+            }
+        }
+
+        let client = Arc::new(Self::with_config(&config)?);
+        *cached = Some((config, client.clone()));
+        Ok(client)
+    }
+
+    /// Run `request` until it succeeds, fails in a way `retry` rejects or
+    /// runs out of retries
+    async fn with_retries<T, F, Fut>(
+        &self,
+        retry: fn(&ApiError) -> bool,
+        mut request: F,
+    ) -> Result<T, ApiError>
+    where
+        F: FnMut() -> Fut,
+        Fut: std::future::Future<Output = Result<T, ApiError>>,
+    {
+        let base = base_backoff();
+        let mut attempt = 0;
+        loop {
+            match request().await {
+                Err(e) if retry(&e) && attempt < MAX_RETRIES => {
+                    tokio::time::sleep(backoff_delay(base, attempt)).await;
+                    attempt += 1;
+                }
+                result => return result,
+            }
+        }
+    }
+
+    pub async fn fetch_usage(&self, access_token: &Secret) -> Result<UsageResponse, ApiError> {
+        self.with_retries(ApiError::is_retryable, || async {
+            let response = self
+                .http
+                .get(usage_endpoint())
+                // `bearer_auth` marks the header sensitive so it is never logged
+                .bearer_auth(access_token.expose())
+                .header("anthropic-beta", ANTHROPIC_BETA)
+                .send()
+                .await?;
+            clock::record_server_date(response.headers());
+
+            if !response.status().is_success() {
+                return Err(error_from_response(response).await);
+            }
+            let body: serde_json::Value = decode(response).await?;
+            let usage = UsageResponse::from_api_value(body).map_err(ApiError::Decode)?;
+            diagnostics::record_schema_drift(&usage.schema_drift);
+            Ok(usage)
+        })
+        .await
+    }
+
+    /// Exchange a refresh token for a new access token. The server rotates
+    /// the refresh token once it sees the request, so this is only retried
+    /// when the request never got there.
+    pub async fn refresh_access_token(
+        &self,
+        refresh_token: &Secret,
+    ) -> Result<OAuthTokenResponse, ApiError> {
+        self.with_retries(ApiError::is_connect_failure, || async {
+            let response = self
+                .http
+                .post(token_endpoint())
+                .json(&serde_json::json!({
+                    "grant_type": "refresh_token",
+                    "refresh_token": refresh_token.expose(),
+                    "client_id": OAUTH_CLIENT_ID,
+                }))
+                .send()
+                .await?;
+            clock::record_server_date(response.headers());
+
+            if !response.status().is_success() {
+                return Err(error_from_response(response).await);
+            }
+            decode(response).await
+        })
+        .await
+    }
+
+    /// Exchange the code from the login redirect for tokens. Codes are
+    /// single-use, so unlike the other requests this is never retried.
+    pub async fn exchange_authorization_code(
+        &self,
+        grant: &AuthorizationCodeGrant<'_>,
+    ) -> Result<OAuthTokenResponse, ApiError> {
+        let response = self
+            .http
+            .post(token_endpoint())
+            .json(&serde_json::json!({
+                "grant_type": "authorization_code",
+                "code": grant.code.expose(),
+                "redirect_uri": grant.redirect_uri,
+                "client_id": OAUTH_CLIENT_ID,
+                "code_verifier": grant.code_verifier.expose(),
+                "state": grant.state,
+            }))
+            .send()
+            .await?;
+        clock::record_server_date(response.headers());
+
+        if !response.status().is_success() {
+            return Err(error_from_response(response).await);
+        }
+        decode(response).await
     }
+}
+
+/// Parameters of the OAuth `authorization_code` grant with PKCE
+pub struct AuthorizationCodeGrant<'a> {
+    pub code: &'a Secret,
+    pub code_verifier: &'a Secret,
+    pub redirect_uri: &'a str,
+    pub state: &'a str,
+}
+
+/// API base URL: environment variable, then settings, then the public API
+pub fn api_base_url() -> String {
+    std::env::var(API_BASE_URL_ENV)
+        .ok()
+        .or_else(|| settings_store::load_settings_or_default().api_base_url)
+        .map(|url| url.trim().trim_end_matches('/').to_string())
+        .filter(|url| !url.is_empty())
+        .unwrap_or_else(|| DEFAULT_API_BASE_URL.to_string())
+}
+
+pub fn usage_endpoint() -> String {
+    format!("{}{}", api_base_url(), USAGE_PATH)
+}
+
+/// Environment variable, then the setting, then the default
+fn oauth_endpoint(env: &str, setting: Option<String>, default: &str) -> String {
+    std::env::var(env)
+        .ok()
+        .or(setting)
+        .map(|url| url.trim().to_string())
+        .filter(|url| !url.is_empty())
+        .unwrap_or_else(|| default.to_string())
+}
+
+pub fn token_endpoint() -> String {
+    let setting = settings_store::load_settings_or_default().oauth.token_url;
+    oauth_endpoint(TOKEN_ENDPOINT_ENV, setting, TOKEN_ENDPOINT)
+}
+
+pub fn authorize_endpoint() -> String {
+    let setting = settings_store::load_settings_or_default()
+        .oauth
+        .authorize_url;
+    oauth_endpoint(AUTHORIZE_ENDPOINT_ENV, setting, AUTHORIZE_ENDPOINT)
+}
+
+pub async fn fetch_usage(access_token: &Secret) -> Result<UsageResponse, ApiError> {
+    UsageClient::shared()?.fetch_usage(access_token).await
+}
+
+pub async fn exchange_authorization_code(
+    grant: &AuthorizationCodeGrant<'_>,
+) -> Result<OAuthTokenResponse, ApiError> {
+    UsageClient::shared()?
+        .exchange_authorization_code(grant)
+        .await
+}
 
-    let usage: UsageResponse = response.json().await?;
-    Ok(usage)
+pub async fn refresh_access_token(refresh_token: &Secret) -> Result<OAuthTokenResponse, ApiError> {
+    UsageClient::shared()?
+        .refresh_access_token(refresh_token)
+        .await
 }
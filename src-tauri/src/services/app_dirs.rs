@@ -0,0 +1,168 @@
+//! Where the app keeps its files: the XDG base directories on Linux, a single
+//! `~/.claude-usage-monitor` folder everywhere else
+
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, PoisonError};
+use thiserror::Error;
+
+const APP_DIR_NAME: &str = "claude-usage-monitor";
+
+/// Neither a home directory nor the XDG variable for a folder is set, so
+/// there is nowhere to keep the app's files
+#[derive(Error, Debug)]
+#[error("No home directory found; set HOME or {0} so settings can be saved")]
+pub struct NoHomeDir(&'static str);
+
+impl From<NoHomeDir> for std::io::Error {
+    fn from(e: NoHomeDir) -> Self {
+        std::io::Error::new(std::io::ErrorKind::NotFound, e)
+    }
+}
+
+/// The folder every file lived in before the XDG layout
+pub fn legacy_dir() -> Option<PathBuf> {
+    dirs::home_dir().map(|home| home.join(".claude-usage-monitor"))
+}
+
+/// `$<var>` when it holds an absolute path, as the XDG spec requires
+fn xdg_dir(var: &str) -> Option<PathBuf> {
+    std::env::var_os(var)
+        .map(PathBuf::from)
+        .filter(|dir| dir.is_absolute())
+}
+
+/// The legacy folder, or the XDG folder when there is no home directory
+fn fallback_dir(xdg_var: &'static str) -> Result<PathBuf, NoHomeDir> {
+    legacy_dir()
+        .or_else(|| xdg_dir(xdg_var).map(|dir| dir.join(APP_DIR_NAME)))
+        .ok_or(NoHomeDir(xdg_var))
+}
+
+/// Settings: `$XDG_CONFIG_HOME/claude-usage-monitor` on Linux
+pub fn config_dir() -> Result<PathBuf, NoHomeDir> {
+    #[cfg(target_os = "linux")]
+    if let Some(dir) = dirs::config_dir() {
+        return Ok(dir.join(APP_DIR_NAME));
+    }
+    fallback_dir("XDG_CONFIG_HOME")
+}
+
+/// Files that can be rebuilt, like the last fetched usage:
+/// `$XDG_CACHE_HOME/claude-usage-monitor` on Linux
+pub fn cache_dir() -> Result<PathBuf, NoHomeDir> {
+    #[cfg(target_os = "linux")]
+    if let Some(dir) = dirs::cache_dir() {
+        return Ok(dir.join(APP_DIR_NAME));
+    }
+    fallback_dir("XDG_CACHE_HOME")
+}
+
+/// Records the app accumulates, like the diagnostics log:
+/// `$XDG_DATA_HOME/claude-usage-monitor` on Linux
+pub fn data_dir() -> Result<PathBuf, NoHomeDir> {
+    #[cfg(target_os = "linux")]
+    if let Some(dir) = dirs::data_dir() {
+        return Ok(dir.join(APP_DIR_NAME));
+    }
+    fallback_dir("XDG_DATA_HOME")
+}
+
+/// Set by `check_available` until the frontend has shown it
+static MISSING: Mutex<Option<String>> = Mutex::new(None);
+
+/// Whether every folder can be found, checked at startup so the user hears
+/// about it before a save fails
+pub fn check_available() -> Result<(), NoHomeDir> {
+    let available = config_dir()
+        .and_then(|_| cache_dir())
+        .and_then(|_| data_dir());
+    if let Err(e) = &available {
+        *MISSING.lock().unwrap_or_else(PoisonError::into_inner) = Some(e.to_string());
+    }
+    available.map(|_| ())
+}
+
+/// The error `check_available` found in this run, returned once
+pub fn take_missing_dirs() -> Option<String> {
+    MISSING
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+        .take()
+}
+
+/// New home for a file from the legacy folder, `None` for files we don't own
+fn destination(file_name: &str) -> Option<PathBuf> {
+    let dir = if file_name.starts_with("settings.json") {
+        config_dir()
+    } else if file_name.starts_with("last_usage.") {
+        cache_dir()
+    } else if file_name.starts_with("diagnostics.log") {
+        data_dir()
+    } else {
+        return None;
+    };
+    dir.ok().map(|dir| dir.join(file_name))
+}
+
+/// `rename` unless the directories are on different file systems
+fn move_file(from: &Path, to: &Path) -> std::io::Result<()> {
+    if let Some(parent) = to.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    if std::fs::rename(from, to).is_ok() {
+        return Ok(());
+    }
+    std::fs::copy(from, to)?;
+    std::fs::remove_file(from)
+}
+
+/// Move files from `~/.claude-usage-monitor` into the platform directories,
+/// then remove the folder once it is empty. A file already present at the
+/// new location wins and the old one is left alone. Runs once at startup,
+/// before anything reads settings. Returns how many files moved.
+pub fn migrate_legacy_dir() -> std::io::Result<usize> {
+    let Some(legacy) = legacy_dir() else {
+        return Ok(0);
+    };
+    let entries = match std::fs::read_dir(&legacy) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(0),
+        Err(e) => return Err(e),
+    };
+
+    let mut moved = 0;
+    for entry in entries {
+        let from = entry?.path();
+        let Some(to) = from
+            .file_name()
+            .and_then(|name| name.to_str())
+            .and_then(destination)
+        else {
+            continue;
+        };
+        if to == from || to.exists() || !from.is_file() {
+            continue;
+        }
+        move_file(&from, &to)?;
+        moved += 1;
+    }
+
+    // Fails, as intended, while anything is left in it
+    let _ = std::fs::remove_dir(&legacy);
+    Ok(moved)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ignores_relative_xdg_dirs() {
+        let var = "CLAUDE_USAGE_MONITOR_TEST_XDG_DIR";
+        std::env::set_var(var, "relative/dir");
+        assert_eq!(xdg_dir(var), None);
+        std::env::set_var(var, "/abs/dir");
+        assert_eq!(xdg_dir(var), Some(PathBuf::from("/abs/dir")));
+        std::env::remove_var(var);
+    }
+}
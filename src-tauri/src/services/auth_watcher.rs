@@ -0,0 +1,122 @@
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use notify::{RecommendedWatcher, RecursiveMode, Watcher};
+use tauri::{AppHandle, Emitter};
+use tokio::sync::mpsc;
+
+use crate::services::settings_store::{self, CredentialSource};
+use crate::services::{auth_status, diagnostics, get_credentials_path};
+
+pub const AUTH_CHANGED_EVENT: &str = "auth-changed";
+
+/// `claude` and editors write in several steps; let them settle before re-reading
+const DEBOUNCE: Duration = Duration::from_millis(150);
+
+/// Settings plus every credentials file a profile reads from
+fn watched_files() -> HashSet<PathBuf> {
+    let settings = settings_store::load_settings_or_default();
+    let mut files: HashSet<PathBuf> = settings_store::get_settings_path().into_iter().collect();
+
+    for profile in settings.effective_profiles() {
+        for source in &profile.credential_providers {
+            match source {
+                CredentialSource::File { path } => {
+                    files.insert(PathBuf::from(path));
+                }
+                CredentialSource::DefaultFile => {
+                    if let Ok(path) = get_credentials_path() {
+                        files.insert(path);
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    files
+}
+
+/// Each file's directory, or its nearest existing ancestor so that creating
+/// `~/.claude` on the first login is noticed too. Watching directories rather
+/// than files also survives the file being replaced by a rename.
+fn watch_dirs(files: &HashSet<PathBuf>) -> HashSet<PathBuf> {
+    files
+        .iter()
+        .filter_map(|file| file.ancestors().skip(1).find(|dir| dir.is_dir()))
+        .map(Path::to_path_buf)
+        .collect()
+}
+
+/// The event touches a watched file or a directory on the way to one
+fn is_relevant(event: &notify::Event, files: &HashSet<PathBuf>) -> bool {
+    if event.kind.is_access() {
+        return false;
+    }
+    event
+        .paths
+        .iter()
+        .any(|path| files.iter().any(|file| file.starts_with(path)))
+}
+
+fn rewatch(
+    watcher: &mut RecommendedWatcher,
+    watching: &mut HashSet<PathBuf>,
+    files: &HashSet<PathBuf>,
+) {
+    let wanted = watch_dirs(files);
+    for dir in watching.difference(&wanted) {
+        let _ = watcher.unwatch(dir);
+    }
+    for dir in wanted.difference(watching) {
+        if let Err(e) = watcher.watch(dir, RecursiveMode::NonRecursive) {
+            diagnostics::record_error("auth_watcher", &format!("{}: {}", dir.display(), e));
+        }
+    }
+    *watching = wanted;
+}
+
+async fn emit_auth_status(app: &AppHandle) {
+    for profile in settings_store::load_settings_or_default().effective_profiles() {
+        let status = auth_status(&profile).await;
+        let _ = app.emit(AUTH_CHANGED_EVENT, status);
+    }
+}
+
+/// Watch the credentials and settings files and emit `auth-changed` with a
+/// fresh `AuthStatus` for every profile whenever one of them changes
+pub fn start(app: AppHandle) {
+    let (tx, mut rx) = mpsc::unbounded_channel();
+    let mut watcher = match notify::recommended_watcher(move |event| {
+        if let Ok(event) = event {
+            let _ = tx.send(event);
+        }
+    }) {
+        Ok(watcher) => watcher,
+        Err(e) => {
+            diagnostics::record_error("auth_watcher", &e.to_string());
+            return;
+        }
+    };
+
+    tauri::async_runtime::spawn(async move {
+        let mut files = watched_files();
+        let mut watching = HashSet::new();
+        rewatch(&mut watcher, &mut watching, &files);
+
+        while let Some(event) = rx.recv().await {
+            if !is_relevant(&event, &files) {
+                continue;
+            }
+
+            tokio::time::sleep(DEBOUNCE).await;
+            while rx.try_recv().is_ok() {}
+
+            // Settings may have added profiles or moved credentials files
+            files = watched_files();
+            rewatch(&mut watcher, &mut watching, &files);
+            emit_auth_status(&app).await;
+        }
+    });
+}
@@ -0,0 +1,149 @@
+//! Locating the `claude` CLI, which GUI apps often can't find on their `PATH`
+
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::time::Duration;
+
+use serde::Serialize;
+
+use crate::services::subprocess;
+
+/// A conservative floor, not a documented cutoff: the first stable CLI
+/// release. Logins from pre-1.0 builds may lack the scopes the usage
+/// endpoint needs.
+pub const MIN_SUPPORTED_VERSION: &str = "1.0.0";
+const VERSION_TIMEOUT: Duration = Duration::from_secs(5);
+
+#[cfg(windows)]
+const BINARY_NAMES: &[&str] = &["claude.exe", "claude.cmd"];
+#[cfg(not(windows))]
+const BINARY_NAMES: &[&str] = &["claude"];
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ClaudeCliInfo {
+    pub missing: bool,
+    pub path: Option<String>,
+    /// `None` when the binary was found but `--version` failed
+    pub version: Option<String>,
+    pub outdated: bool,
+    pub min_version: String,
+}
+
+/// Where installers put `claude` outside of the usual `PATH` of a GUI app
+fn common_locations() -> Vec<PathBuf> {
+    let mut locations = Vec::new();
+
+    if let Some(prefix) = std::env::var_os("NPM_CONFIG_PREFIX") {
+        let prefix = PathBuf::from(prefix);
+        locations.push(prefix.join("bin"));
+        locations.push(prefix);
+    }
+    if let Some(home) = dirs::home_dir() {
+        // Native installer and `claude migrate-installer`
+        locations.push(home.join(".local/bin"));
+        locations.push(home.join(".claude/local"));
+        locations.push(home.join(".npm-global/bin"));
+        locations.push(home.join(".volta/bin"));
+        locations.push(home.join(".bun/bin"));
+        // Every Node version managed by nvm, newest first
+        if let Ok(entries) = std::fs::read_dir(home.join(".nvm/versions/node")) {
+            let mut versions: Vec<PathBuf> = entries.flatten().map(|e| e.path()).collect();
+            versions.sort_by(|a, b| compare_versions(&dir_version(b), &dir_version(a)));
+            locations.extend(versions.into_iter().map(|v| v.join("bin")));
+        }
+    }
+    #[cfg(windows)]
+    if let Some(app_data) = std::env::var_os("APPDATA") {
+        locations.push(PathBuf::from(app_data).join("npm"));
+    }
+    #[cfg(not(windows))]
+    {
+        locations.push(PathBuf::from("/opt/homebrew/bin"));
+        locations.push(PathBuf::from("/usr/local/bin"));
+    }
+
+    locations
+}
+
+fn dir_version(dir: &Path) -> String {
+    dir.file_name()
+        .and_then(|name| name.to_str())
+        .unwrap_or_default()
+        .trim_start_matches('v')
+        .to_string()
+}
+
+/// First `claude` binary on `PATH` or in a common install location
+pub fn find() -> Option<PathBuf> {
+    let path_dirs = std::env::var_os("PATH")
+        .map(|paths| std::env::split_paths(&paths).collect::<Vec<_>>())
+        .unwrap_or_default();
+
+    path_dirs
+        .into_iter()
+        .chain(common_locations())
+        .flat_map(|dir| BINARY_NAMES.iter().map(move |name| dir.join(name)))
+        .find(|candidate| candidate.is_file())
+}
+
+/// Program to run for `claude`: the detected binary, or the bare name so the
+/// error message names what is missing
+pub fn command() -> String {
+    find()
+        .map(|path| path.display().to_string())
+        .unwrap_or_else(|| BINARY_NAMES[0].trim_end_matches(".exe").to_string())
+}
+
+/// `1.0.51 (Claude Code)` → `1.0.51`
+fn parse_version(output: &str) -> Option<String> {
+    output
+        .split_whitespace()
+        .map(|word| word.trim_start_matches('v'))
+        .find(|word| {
+            word.split('.').count() >= 2
+                && word
+                    .split('.')
+                    .all(|part| !part.is_empty() && part.chars().all(|c| c.is_ascii_digit()))
+        })
+        .map(str::to_string)
+}
+
+/// Numeric comparison of dotted versions; missing parts count as 0
+fn compare_versions(a: &str, b: &str) -> std::cmp::Ordering {
+    let parts = |v: &str| -> Vec<u64> {
+        v.split(['.', '-', '+'])
+            .map_while(|part| part.parse().ok())
+            .collect()
+    };
+    let (a, b) = (parts(a), parts(b));
+    let len = a.len().max(b.len());
+    (0..len)
+        .map(|i| a.get(i).unwrap_or(&0).cmp(b.get(i).unwrap_or(&0)))
+        .find(|ordering| ordering.is_ne())
+        .unwrap_or(std::cmp::Ordering::Equal)
+}
+
+/// Run `claude --version`, killing it if it hangs (e.g. waiting on a prompt)
+fn read_version(path: &Path) -> Option<String> {
+    let output =
+        subprocess::output_with_timeout(Command::new(path).arg("--version"), None, VERSION_TIMEOUT)
+            .ok()?;
+    parse_version(&String::from_utf8_lossy(&output.stdout))
+}
+
+/// Locate the CLI and read its version. Blocks for up to `VERSION_TIMEOUT`.
+pub fn detect() -> ClaudeCliInfo {
+    let path = find();
+    let version = path.as_deref().and_then(read_version);
+    let outdated = version
+        .as_deref()
+        .is_some_and(|v| compare_versions(v, MIN_SUPPORTED_VERSION).is_lt());
+
+    ClaudeCliInfo {
+        missing: path.is_none(),
+        path: path.map(|p| p.display().to_string()),
+        version,
+        outdated,
+        min_version: MIN_SUPPORTED_VERSION.to_string(),
+    }
+}
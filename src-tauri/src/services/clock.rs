@@ -0,0 +1,42 @@
+use std::sync::atomic::{AtomicBool, AtomicI64, Ordering};
+
+use reqwest::header::{HeaderMap, DATE};
+
+/// Skew large enough to warn that validity shown in the app may be wrong.
+/// `expiresAt` is written against the local clock, so a steady offset is
+/// harmless, but a drifting VM clock makes it unreliable.
+pub const SKEW_WARNING_MS: i64 = 60_000;
+
+static SKEW_MS: AtomicI64 = AtomicI64::new(0);
+static SKEW_KNOWN: AtomicBool = AtomicBool::new(false);
+
+/// Compare the local clock with an API response's `Date` header
+pub fn record_server_date(headers: &HeaderMap) {
+    let Some(date) = headers
+        .get(DATE)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| chrono::DateTime::parse_from_rfc2822(value).ok())
+    else {
+        return;
+    };
+
+    let skew = date.timestamp_millis() - chrono::Utc::now().timestamp_millis();
+    SKEW_MS.store(skew, Ordering::Relaxed);
+    SKEW_KNOWN.store(true, Ordering::Relaxed);
+}
+
+/// How far the server's clock is ahead of ours (negative when behind), once
+/// an API response has been seen. `Date` has one-second resolution and
+/// arrives after network latency, so only large values mean anything.
+pub fn skew_ms() -> Option<i64> {
+    SKEW_KNOWN
+        .load(Ordering::Relaxed)
+        .then(|| SKEW_MS.load(Ordering::Relaxed))
+}
+
+/// Skew worth warning about, in whole seconds
+pub fn significant_skew_secs() -> Option<i64> {
+    skew_ms()
+        .filter(|skew| skew.abs() >= SKEW_WARNING_MS)
+        .map(|skew| skew / 1000)
+}
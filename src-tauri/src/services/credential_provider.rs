@@ -0,0 +1,251 @@
+use std::path::PathBuf;
+use std::process::Command;
+use std::time::Duration;
+
+use zeroize::Zeroizing;
+
+use crate::models::{ClaudeOAuth, CredentialsFile, Secret};
+use crate::services::credential_store::{
+    get_credentials_path, read_credentials_file, write_credentials_file, CredentialError, NO_EXPIRY,
+};
+use crate::services::settings_store::CredentialSource;
+use crate::services::{redact, subprocess};
+
+/// How long a `credential_process` command gets to print credentials
+const PROCESS_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// One place credentials can come from. Providers are tried in the order
+/// configured in settings and the first one that yields a token wins.
+pub trait CredentialProvider: Send + Sync {
+    /// Short identifier reported in `AuthStatus`
+    fn name(&self) -> &'static str;
+
+    /// Where this provider looks, for diagnostics (path, variable, command)
+    fn location(&self) -> String;
+
+    fn read(&self) -> Result<ClaudeOAuth, CredentialError>;
+
+    /// Whether refreshed tokens can be written back
+    fn can_store(&self) -> bool {
+        false
+    }
+
+    fn store(&self, _oauth: &ClaudeOAuth) -> Result<(), CredentialError> {
+        Err(CredentialError::WriteError(format!(
+            "{} credentials are read-only",
+            self.name()
+        )))
+    }
+}
+
+/// Bare access token from an environment variable, e.g. `CLAUDE_CODE_OAUTH_TOKEN`
+pub struct EnvTokenProvider {
+    pub var: String,
+}
+
+impl CredentialProvider for EnvTokenProvider {
+    fn name(&self) -> &'static str {
+        "env"
+    }
+
+    fn location(&self) -> String {
+        format!("${}", self.var)
+    }
+
+    fn read(&self) -> Result<ClaudeOAuth, CredentialError> {
+        let token = std::env::var(&self.var)
+            .ok()
+            .map(|token| token.trim().to_string())
+            .filter(|token| !token.is_empty())
+            .ok_or_else(|| CredentialError::Unavailable(format!("{} is not set", self.var)))?;
+
+        // Long-lived tokens carry no expiry or refresh token
+        Ok(ClaudeOAuth {
+            access_token: Secret::new(token),
+            refresh_token: None,
+            expires_at: NO_EXPIRY,
+            scopes: None,
+        })
+    }
+}
+
+/// A Claude Code `.credentials.json` file
+pub struct FileProvider {
+    /// `None` when the default location could not be determined
+    pub path: Option<PathBuf>,
+    /// `file` for a user-configured path, `default_file` for the standard one
+    pub kind: &'static str,
+}
+
+impl CredentialProvider for FileProvider {
+    fn name(&self) -> &'static str {
+        self.kind
+    }
+
+    fn location(&self) -> String {
+        match &self.path {
+            Some(path) => path.display().to_string(),
+            None => "<unknown>".to_string(),
+        }
+    }
+
+    fn read(&self) -> Result<ClaudeOAuth, CredentialError> {
+        let path = self.path.as_ref().ok_or(CredentialError::NoHomeDirectory)?;
+        read_credentials_file(path)
+    }
+
+    fn can_store(&self) -> bool {
+        self.path.is_some()
+    }
+
+    fn store(&self, oauth: &ClaudeOAuth) -> Result<(), CredentialError> {
+        let path = self.path.as_ref().ok_or(CredentialError::NoHomeDirectory)?;
+        write_credentials_file(path, oauth)
+    }
+}
+
+/// The `Claude Code-credentials` entry in the macOS Keychain
+pub struct KeychainProvider;
+
+impl CredentialProvider for KeychainProvider {
+    fn name(&self) -> &'static str {
+        "keychain"
+    }
+
+    fn location(&self) -> String {
+        "macOS Keychain".to_string()
+    }
+
+    #[cfg(target_os = "macos")]
+    fn read(&self) -> Result<ClaudeOAuth, CredentialError> {
+        crate::services::credential_store::read_from_keychain()
+    }
+
+    #[cfg(not(target_os = "macos"))]
+    fn read(&self) -> Result<ClaudeOAuth, CredentialError> {
+        Err(CredentialError::Unavailable(
+            "the Keychain is only available on macOS".to_string(),
+        ))
+    }
+
+    fn can_store(&self) -> bool {
+        cfg!(target_os = "macos")
+    }
+
+    #[cfg(target_os = "macos")]
+    fn store(&self, oauth: &ClaudeOAuth) -> Result<(), CredentialError> {
+        crate::services::credential_store::write_to_keychain(oauth)
+    }
+}
+
+/// An item in the Linux Secret Service, holding the same JSON document as
+/// the credentials file
+pub struct SecretServiceProvider {
+    pub service: String,
+}
+
+impl CredentialProvider for SecretServiceProvider {
+    fn name(&self) -> &'static str {
+        "secret_service"
+    }
+
+    fn location(&self) -> String {
+        format!("Secret Service ({})", self.service)
+    }
+
+    #[cfg(target_os = "linux")]
+    fn read(&self) -> Result<ClaudeOAuth, CredentialError> {
+        crate::services::secret_service::read(&self.service)
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    fn read(&self) -> Result<ClaudeOAuth, CredentialError> {
+        Err(CredentialError::Unavailable(
+            "the Secret Service is only available on Linux".to_string(),
+        ))
+    }
+
+    fn can_store(&self) -> bool {
+        cfg!(target_os = "linux")
+    }
+
+    #[cfg(target_os = "linux")]
+    fn store(&self, oauth: &ClaudeOAuth) -> Result<(), CredentialError> {
+        crate::services::secret_service::store(&self.service, oauth)
+    }
+}
+
+/// External command that prints credentials JSON on stdout, either the
+/// `.credentials.json` layout or a bare `claudeAiOauth` object
+pub struct ProcessProvider {
+    pub command: String,
+}
+
+impl CredentialProvider for ProcessProvider {
+    fn name(&self) -> &'static str {
+        "process"
+    }
+
+    fn location(&self) -> String {
+        self.command.clone()
+    }
+
+    fn read(&self) -> Result<ClaudeOAuth, CredentialError> {
+        if self.command.trim().is_empty() {
+            return Err(CredentialError::Unavailable(
+                "no credential_process command configured".to_string(),
+            ));
+        }
+
+        #[cfg(target_os = "windows")]
+        let mut command = Command::new("cmd");
+        #[cfg(target_os = "windows")]
+        command.args(["/C", &self.command]);
+        #[cfg(not(target_os = "windows"))]
+        let mut command = Command::new("sh");
+        #[cfg(not(target_os = "windows"))]
+        command.args(["-c", &self.command]);
+
+        let output = subprocess::output_with_timeout(&mut command, None, PROCESS_TIMEOUT)
+            .map_err(|e| CredentialError::ProcessError(e.to_string()))?;
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            return Err(CredentialError::ProcessError(format!(
+                "exited with {}: {}",
+                output.status,
+                redact::scrub(stderr.trim())
+            )));
+        }
+
+        let stdout = Zeroizing::new(String::from_utf8_lossy(&output.stdout).into_owned());
+        let value: serde_json::Value = serde_json::from_str(stdout.trim())?;
+        if value.get("claudeAiOauth").is_some() {
+            let creds: CredentialsFile = serde_json::from_value(value)?;
+            return creds
+                .claude_ai_oauth
+                .ok_or(CredentialError::NoOAuthCredentials);
+        }
+        Ok(serde_json::from_value(value)?)
+    }
+}
+
+pub fn build_provider(source: &CredentialSource) -> Box<dyn CredentialProvider> {
+    match source {
+        CredentialSource::Env { var } => Box::new(EnvTokenProvider { var: var.clone() }),
+        CredentialSource::File { path } => Box::new(FileProvider {
+            path: Some(PathBuf::from(path)),
+            kind: "file",
+        }),
+        CredentialSource::DefaultFile => Box::new(FileProvider {
+            path: get_credentials_path().ok(),
+            kind: "default_file",
+        }),
+        CredentialSource::Keychain => Box::new(KeychainProvider),
+        CredentialSource::SecretService { service } => Box::new(SecretServiceProvider {
+            service: service.clone(),
+        }),
+        CredentialSource::Process { command } => Box::new(ProcessProvider {
+            command: command.clone(),
+        }),
+    }
+}
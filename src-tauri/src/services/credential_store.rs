@@ -1,8 +1,36 @@
-use std::path::PathBuf;
-use std::process::Command;
+use std::path::{Path, PathBuf};
+#[cfg(target_os = "macos")]
+use std::process::{Command, Output};
+use std::sync::Arc;
+#[cfg(target_os = "macos")]
+use std::time::Duration;
 use thiserror::Error;
+use zeroize::Zeroizing;
+
+use crate::models::{
+    AuthStatus, ClaudeOAuth, CredentialsFile, FilePermissionWarning, OAuthTokenResponse,
+    ProviderAttempt,
+};
+use crate::services::credential_provider::{build_provider, CredentialProvider};
+use crate::services::settings_store::{self, Profile};
+use crate::services::{anthropic_api, clock, diagnostics};
+
+#[cfg(target_os = "macos")]
+const KEYCHAIN_SERVICE: &str = "Claude Code-credentials";
+
+/// Leaves time to answer a Keychain access prompt
+#[cfg(target_os = "macos")]
+const KEYCHAIN_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Refresh this long before `expires_at` so a request never races the expiry
+pub(crate) const REFRESH_MARGIN_MS: i64 = 5 * 60 * 1000;
+
+/// Held for the whole of a refresh. Refresh tokens rotate, so two refreshes
+/// spending the same one would leave the loser with a revoked token.
+static REFRESH_LOCK: tokio::sync::Mutex<()> = tokio::sync::Mutex::const_new(());
 
-use crate::models::{ClaudeOAuth, CredentialsFile};
+/// `expires_at` for tokens that carry no expiry, such as ones from the environment
+pub const NO_EXPIRY: i64 = i64::MAX;
 
 #[derive(Error, Debug)]
 pub enum CredentialError {
@@ -20,26 +48,127 @@ pub enum CredentialError {
 
     #[error("Failed to read from keychain: {0}")]
     KeychainError(String),
+
+    #[error("Secret Service error: {0}")]
+    SecretServiceError(String),
+
+    #[error("No refresh token available")]
+    NoRefreshToken,
+
+    #[error("Failed to refresh token: {0}")]
+    RefreshError(String),
+
+    #[error("Failed to write credentials: {0}")]
+    WriteError(String),
+
+    #[error("Credential process failed: {0}")]
+    ProcessError(String),
+
+    #[error("{0}")]
+    Unavailable(String),
+
+    #[error("Could not determine the home directory; set CLAUDE_CONFIG_DIR or a credentials path")]
+    NoHomeDirectory,
 }
 
-pub fn get_credentials_path() -> PathBuf {
-    let home = dirs::home_dir().expect("Could not find home directory");
-    home.join(".claude").join(".credentials.json")
+impl CredentialError {
+    /// Machine-readable reason reported in `AuthStatus.error_reason`
+    pub fn reason(&self) -> &'static str {
+        match self {
+            Self::NotFound | Self::Unavailable(_) => "not_found",
+            Self::NoOAuthCredentials => "no_oauth",
+            Self::ReadError(_) => "read_error",
+            Self::ParseError(_) => "parse_error",
+            Self::KeychainError(_) => "keychain_error",
+            Self::SecretServiceError(_) => "secret_service_error",
+            Self::NoRefreshToken => "token_expired",
+            Self::RefreshError(_) => "refresh_failed",
+            Self::WriteError(_) => "write_error",
+            Self::ProcessError(_) => "process_error",
+            Self::NoHomeDirectory => "no_home_dir",
+        }
+    }
+
+    /// The provider simply had nothing to offer, as opposed to failing
+    fn is_absent(&self) -> bool {
+        matches!(self, Self::NotFound | Self::Unavailable(_))
+    }
+}
+
+/// Credentials together with the provider that supplied them
+pub struct ResolvedCredentials {
+    pub oauth: ClaudeOAuth,
+    pub provider: Arc<dyn CredentialProvider>,
+    pub skipped: Vec<ProviderAttempt>,
+}
+
+/// Every provider in the chain came up empty
+#[derive(Debug)]
+pub struct CredentialChainError {
+    pub error: CredentialError,
+    pub skipped: Vec<ProviderAttempt>,
+}
+
+/// Claude Code keeps its config, including credentials, here instead of `~/.claude`
+const CLAUDE_CONFIG_DIR_ENV: &str = "CLAUDE_CONFIG_DIR";
+const CREDENTIALS_FILE_NAME: &str = ".credentials.json";
+
+/// Expand a leading `~` so settings can use home-relative paths
+fn expand_home(path: &str) -> Result<PathBuf, CredentialError> {
+    match path.strip_prefix('~') {
+        Some(rest) if rest.is_empty() || rest.starts_with('/') || rest.starts_with('\\') => {
+            let home = dirs::home_dir().ok_or(CredentialError::NoHomeDirectory)?;
+            Ok(home.join(rest.trim_start_matches(['/', '\\'])))
+        }
+        _ => Ok(PathBuf::from(path)),
+    }
+}
+
+/// Credentials file location: the `credentials_path` setting, then
+/// `$CLAUDE_CONFIG_DIR/.credentials.json`, then `~/.claude/.credentials.json`
+pub fn get_credentials_path() -> Result<PathBuf, CredentialError> {
+    let settings = settings_store::load_settings_or_default();
+    if let Some(path) = settings
+        .credentials_path
+        .as_deref()
+        .map(str::trim)
+        .filter(|path| !path.is_empty())
+    {
+        return expand_home(path);
+    }
+
+    if let Some(dir) = std::env::var_os(CLAUDE_CONFIG_DIR_ENV).filter(|dir| !dir.is_empty()) {
+        return Ok(expand_home(&dir.to_string_lossy())?.join(CREDENTIALS_FILE_NAME));
+    }
+
+    let home = dirs::home_dir().ok_or(CredentialError::NoHomeDirectory)?;
+    Ok(home.join(".claude").join(CREDENTIALS_FILE_NAME))
+}
+
+/// Run the `security` CLI, which can sit waiting on an access prompt
+#[cfg(target_os = "macos")]
+fn security(args: &[&str], input: Option<&[u8]>) -> Result<Output, CredentialError> {
+    crate::services::subprocess::output_with_timeout(
+        Command::new("security").args(args),
+        input,
+        KEYCHAIN_TIMEOUT,
+    )
+    .map_err(|e| CredentialError::KeychainError(e.to_string()))
 }
 
 /// Read credentials from macOS Keychain
 #[cfg(target_os = "macos")]
-fn read_from_keychain() -> Result<ClaudeOAuth, CredentialError> {
-    let output = Command::new("security")
-        .args(["find-generic-password", "-s", "Claude Code-credentials", "-w"])
-        .output()
-        .map_err(|e| CredentialError::KeychainError(e.to_string()))?;
+pub(crate) fn read_from_keychain() -> Result<ClaudeOAuth, CredentialError> {
+    let output = security(
+        &["find-generic-password", "-s", KEYCHAIN_SERVICE, "-w"],
+        None,
+    )?;
 
     if !output.status.success() {
         return Err(CredentialError::NotFound);
     }
 
-    let content = String::from_utf8_lossy(&output.stdout);
+    let content = Zeroizing::new(String::from_utf8_lossy(&output.stdout).into_owned());
     let creds: CredentialsFile = serde_json::from_str(content.trim())?;
 
     creds
@@ -47,29 +176,476 @@ fn read_from_keychain() -> Result<ClaudeOAuth, CredentialError> {
         .ok_or(CredentialError::NoOAuthCredentials)
 }
 
-pub fn read_credentials() -> Result<ClaudeOAuth, CredentialError> {
-    // First try reading from file (works on Linux/Windows)
-    let path = get_credentials_path();
+/// Account of the existing Keychain entry, parsed from the `"acct"<blob>=`
+/// line `security find-generic-password` prints
+#[cfg(target_os = "macos")]
+fn keychain_account() -> Option<String> {
+    let output = security(&["find-generic-password", "-s", KEYCHAIN_SERVICE], None).ok()?;
+    if !output.status.success() {
+        return None;
+    }
 
-    if path.exists() {
-        let content = std::fs::read_to_string(&path)?;
-        let creds: CredentialsFile = serde_json::from_str(&content)?;
-        if let Some(oauth) = creds.claude_ai_oauth {
-            return Ok(oauth);
-        }
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .find_map(|line| line.trim().strip_prefix("\"acct\"<blob>=\""))
+        .and_then(|rest| rest.strip_suffix('"'))
+        .map(str::to_string)
+}
+
+/// Quote an argument for the command line `security -i` reads
+#[cfg(target_os = "macos")]
+fn security_quote(value: &str) -> String {
+    format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+/// Write refreshed tokens back to the macOS Keychain entry. The command goes
+/// to `security -i` on stdin, so the secret never shows up in `ps`.
+#[cfg(target_os = "macos")]
+pub(crate) fn write_to_keychain(oauth: &ClaudeOAuth) -> Result<(), CredentialError> {
+    use std::fmt::Write as _;
+
+    let output = security(
+        &["find-generic-password", "-s", KEYCHAIN_SERVICE, "-w"],
+        None,
+    )?;
+
+    let existing = Zeroizing::new(String::from_utf8_lossy(&output.stdout).into_owned());
+    let content = merge_oauth_json(existing.trim(), oauth)?;
+    // Keep the entry under the account Claude Code created it with; `-U`
+    // would otherwise add a second item next to it
+    let account = keychain_account()
+        .or_else(|| std::env::var("USER").ok())
+        .unwrap_or_default();
+
+    // `-X` takes the password hex-encoded, which needs no quoting
+    let mut command = Zeroizing::new(format!(
+        "add-generic-password -U -s {} -a {} -X ",
+        security_quote(KEYCHAIN_SERVICE),
+        security_quote(&account)
+    ));
+    for byte in content.as_bytes() {
+        let _ = write!(command, "{:02x}", byte);
     }
+    command.push('\n');
 
-    // On macOS, try reading from Keychain
-    #[cfg(target_os = "macos")]
-    {
-        return read_from_keychain();
+    let output = security(&["-i"], Some(command.as_bytes()))?;
+
+    // Interactive mode exits 0 even when a command fails, so check stderr too
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    if !output.status.success() || !stderr.trim().is_empty() {
+        return Err(CredentialError::WriteError(format!(
+            "security add-generic-password failed: {}",
+            stderr.trim()
+        )));
     }
+    Ok(())
+}
 
-    #[cfg(not(target_os = "macos"))]
-    Err(CredentialError::NotFound)
+pub(crate) fn read_credentials_file(path: &Path) -> Result<ClaudeOAuth, CredentialError> {
+    if !path.exists() {
+        return Err(CredentialError::NotFound);
+    }
+
+    let content = Zeroizing::new(std::fs::read_to_string(path)?);
+    let creds: CredentialsFile = serde_json::from_str(&content)?;
+    creds
+        .claude_ai_oauth
+        .ok_or(CredentialError::NoOAuthCredentials)
+}
+
+/// Warn when other users can read the credentials file or it belongs to someone else
+#[cfg(unix)]
+pub fn audit_credentials_permissions(path: &Path) -> Option<FilePermissionWarning> {
+    use std::os::unix::fs::MetadataExt;
+
+    let metadata = std::fs::metadata(path).ok()?;
+    let mode = metadata.mode() & 0o777;
+    // SAFETY: geteuid has no preconditions and cannot fail
+    let euid = unsafe { libc::geteuid() };
+
+    let reason = if metadata.uid() != euid {
+        "wrong_owner"
+    } else if mode & 0o004 != 0 {
+        "world_readable"
+    } else if mode & 0o040 != 0 {
+        "group_readable"
+    } else {
+        return None;
+    };
+
+    Some(FilePermissionWarning {
+        path: path.display().to_string(),
+        mode: format!("{:04o}", mode),
+        reason: reason.to_string(),
+        // chmod cannot change the owner
+        fixable: reason != "wrong_owner",
+    })
+}
+
+#[cfg(not(unix))]
+pub fn audit_credentials_permissions(_path: &Path) -> Option<FilePermissionWarning> {
+    None
+}
+
+/// Restrict the credentials file to its owner (mode 0600)
+#[cfg(unix)]
+pub fn restrict_credentials_permissions(path: &Path) -> Result<(), CredentialError> {
+    use std::os::unix::fs::PermissionsExt;
+
+    std::fs::set_permissions(path, std::fs::Permissions::from_mode(0o600))
+        .map_err(|e| CredentialError::WriteError(e.to_string()))
+}
+
+#[cfg(not(unix))]
+pub fn restrict_credentials_permissions(_path: &Path) -> Result<(), CredentialError> {
+    Err(CredentialError::WriteError(
+        "file modes can only be changed on Unix".to_string(),
+    ))
+}
+
+/// Run blocking provider work, such as a command waiting on a keyring
+/// prompt, on the blocking thread pool instead of the async runtime
+async fn run_blocking<T, F>(work: F) -> Result<T, CredentialError>
+where
+    F: FnOnce() -> Result<T, CredentialError> + Send + 'static,
+    T: Send + 'static,
+{
+    tauri::async_runtime::spawn_blocking(work)
+        .await
+        .map_err(|e| CredentialError::Unavailable(e.to_string()))?
+}
+
+/// Try each of the profile's providers in order until one yields
+/// credentials. Providers may run commands, so this can block for a while;
+/// async code uses `resolve_credentials`.
+pub fn resolve_credentials_blocking(
+    profile: &Profile,
+) -> Result<ResolvedCredentials, CredentialChainError> {
+    let mut skipped = Vec::new();
+    let mut first_failure = None;
+
+    for source in &profile.credential_providers {
+        let provider: Arc<dyn CredentialProvider> = Arc::from(build_provider(source));
+        match provider.read() {
+            Ok(oauth) => {
+                return Ok(ResolvedCredentials {
+                    oauth,
+                    provider,
+                    skipped,
+                })
+            }
+            Err(e) => {
+                skipped.push(ProviderAttempt {
+                    provider: provider.name().to_string(),
+                    location: provider.location(),
+                    reason: e.to_string(),
+                });
+                if first_failure.is_none() && !e.is_absent() {
+                    first_failure = Some(e);
+                }
+            }
+        }
+    }
+
+    // A provider that failed outright explains more than "not found"
+    Err(CredentialChainError {
+        error: first_failure.unwrap_or(CredentialError::NotFound),
+        skipped,
+    })
+}
+
+/// `resolve_credentials_blocking` on the blocking thread pool
+pub async fn resolve_credentials(
+    profile: &Profile,
+) -> Result<ResolvedCredentials, CredentialChainError> {
+    let profile = profile.clone();
+    tauri::async_runtime::spawn_blocking(move || resolve_credentials_blocking(&profile))
+        .await
+        .unwrap_or_else(|e| {
+            Err(CredentialChainError {
+                error: CredentialError::Unavailable(e.to_string()),
+                skipped: Vec::new(),
+            })
+        })
 }
 
 pub fn is_token_valid(creds: &ClaudeOAuth) -> bool {
     let now = chrono::Utc::now().timestamp_millis();
     creds.expires_at > now
 }
+
+/// Whether the token expires within `REFRESH_MARGIN_MS`
+pub fn needs_refresh(creds: &ClaudeOAuth) -> bool {
+    let now = chrono::Utc::now().timestamp_millis();
+    creds.expires_at.saturating_sub(REFRESH_MARGIN_MS) <= now
+}
+
+/// Scopes the usage endpoint needs that the token was not granted. Tokens
+/// that don't list their scopes, such as ones from the environment, pass.
+pub fn missing_scopes(creds: &ClaudeOAuth) -> Vec<String> {
+    let Some(scopes) = &creds.scopes else {
+        return Vec::new();
+    };
+    anthropic_api::USAGE_REQUIRED_SCOPES
+        .iter()
+        .filter(|required| !scopes.iter().any(|scope| scope == *required))
+        .map(|required| required.to_string())
+        .collect()
+}
+
+/// Update the `claudeAiOauth` entry of a credentials document, keeping every
+/// other field, the key order and the compact/pretty layout as they were
+pub(crate) fn merge_oauth_json(
+    content: &str,
+    oauth: &ClaudeOAuth,
+) -> Result<Zeroizing<String>, CredentialError> {
+    let mut doc: serde_json::Value = if content.trim().is_empty() {
+        serde_json::json!({})
+    } else {
+        serde_json::from_str(content)?
+    };
+
+    let root = doc
+        .as_object_mut()
+        .ok_or(CredentialError::NoOAuthCredentials)?;
+    let entry = root
+        .entry("claudeAiOauth")
+        .or_insert_with(|| serde_json::json!({}));
+    let entry = entry
+        .as_object_mut()
+        .ok_or(CredentialError::NoOAuthCredentials)?;
+
+    entry.insert("accessToken".into(), oauth.access_token.expose().into());
+    if let Some(refresh_token) = &oauth.refresh_token {
+        entry.insert("refreshToken".into(), refresh_token.expose().into());
+    }
+    entry.insert("expiresAt".into(), oauth.expires_at.into());
+    if let Some(scopes) = &oauth.scopes {
+        entry.insert("scopes".into(), scopes.clone().into());
+    }
+
+    let mut output = Zeroizing::new(if content.trim().contains('\n') {
+        serde_json::to_string_pretty(&doc)?
+    } else {
+        serde_json::to_string(&doc)?
+    });
+    if content.ends_with('\n') {
+        output.push('\n');
+    }
+    Ok(output)
+}
+
+/// Update a credentials file in place with refreshed tokens
+pub(crate) fn write_credentials_file(
+    path: &Path,
+    oauth: &ClaudeOAuth,
+) -> Result<(), CredentialError> {
+    let write_error = |e: std::io::Error| CredentialError::WriteError(e.to_string());
+
+    if !path.exists() {
+        use std::io::Write;
+
+        // First login on this machine: the file is its owner's alone from the start
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent).map_err(write_error)?;
+        }
+        let content = merge_oauth_json("", oauth)?;
+        let mut file = new_private_file().open(path).map_err(write_error)?;
+        file.write_all(content.as_bytes()).map_err(write_error)?;
+        return file.sync_all().map_err(write_error);
+    }
+
+    let content = Zeroizing::new(std::fs::read_to_string(path)?);
+    let updated = merge_oauth_json(&content, oauth)?;
+    replace_file(path, updated.as_bytes()).map_err(write_error)
+}
+
+/// Options for a file only its owner can read, from the moment it exists
+fn new_private_file() -> std::fs::OpenOptions {
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+    options
+}
+
+/// Replace the file's contents through a temp file in the same directory,
+/// renamed over it once synced, so a crash never leaves it truncated. The
+/// new file gets the original's permissions.
+fn replace_file(path: &Path, content: &[u8]) -> std::io::Result<()> {
+    use std::io::Write;
+
+    // Through a symlink, replace the file it points to rather than the link
+    let path = std::fs::canonicalize(path)?;
+    let permissions = std::fs::metadata(&path)?.permissions();
+    let mut tmp_name = path.file_name().unwrap_or_default().to_os_string();
+    tmp_name.push(".tmp");
+    let tmp = path.with_file_name(tmp_name);
+
+    // Left over from an interrupted write
+    let _ = std::fs::remove_file(&tmp);
+    let written = new_private_file().open(&tmp).and_then(|mut file| {
+        file.write_all(content)?;
+        file.set_permissions(permissions)?;
+        file.sync_all()
+    });
+    if let Err(e) = written.and_then(|()| std::fs::rename(&tmp, &path)) {
+        let _ = std::fs::remove_file(&tmp);
+        return Err(e);
+    }
+    Ok(())
+}
+
+/// Credentials from a token endpoint response, keeping the refresh token and
+/// scopes of `previous` when the response leaves them out
+pub(crate) fn oauth_from_token_response(
+    token: OAuthTokenResponse,
+    previous: Option<&ClaudeOAuth>,
+) -> ClaudeOAuth {
+    ClaudeOAuth {
+        access_token: token.access_token,
+        refresh_token: token
+            .refresh_token
+            .or_else(|| previous.and_then(|creds| creds.refresh_token.clone())),
+        expires_at: chrono::Utc::now().timestamp_millis() + token.expires_in * 1000,
+        scopes: token
+            .scope
+            .map(|scope| scope.split_whitespace().map(str::to_string).collect())
+            .or_else(|| previous.and_then(|creds| creds.scopes.clone())),
+    }
+}
+
+/// Exchange the stored refresh token for a new access token and save it
+/// back to the provider it came from, when that provider can store tokens.
+/// Refreshes run one at a time, and each re-reads the provider first: when
+/// another refresh, or the Claude CLI, already replaced `resolved.oauth`,
+/// the newer tokens are returned without spending the refresh token again.
+pub async fn refresh_credentials(
+    resolved: &ResolvedCredentials,
+) -> Result<ClaudeOAuth, CredentialError> {
+    let _refreshing = REFRESH_LOCK.lock().await;
+
+    let provider = resolved.provider.clone();
+    let creds = run_blocking(move || provider.read()).await?;
+    if creds.access_token != resolved.oauth.access_token && !needs_refresh(&creds) {
+        return Ok(creds);
+    }
+    let refresh_token = creds
+        .refresh_token
+        .as_ref()
+        .ok_or(CredentialError::NoRefreshToken)?;
+
+    let token = anthropic_api::refresh_access_token(refresh_token)
+        .await
+        .map_err(|e| CredentialError::RefreshError(e.to_string()))?;
+
+    let refreshed = oauth_from_token_response(token, Some(&creds));
+
+    if resolved.provider.can_store() {
+        let provider = resolved.provider.clone();
+        let stored = refreshed.clone();
+        run_blocking(move || provider.store(&stored)).await?;
+    }
+    Ok(refreshed)
+}
+
+/// Refresh the access token within `REFRESH_MARGIN_MS` of its expiry, so
+/// no request goes out with a lapsed token. A token the Claude CLI renewed
+/// meanwhile is picked up by `refresh_credentials` instead of refreshing again.
+pub async fn refresh_if_needed(
+    mut resolved: ResolvedCredentials,
+) -> Result<ResolvedCredentials, CredentialError> {
+    if !needs_refresh(&resolved.oauth) || resolved.oauth.refresh_token.is_none() {
+        return Ok(resolved);
+    }
+
+    match refresh_credentials(&resolved).await {
+        Ok(oauth) => resolved.oauth = oauth,
+        // Still usable; the next call tries again before it lapses
+        Err(e) if is_token_valid(&resolved.oauth) => {
+            diagnostics::record_error("auth", &format!("early token refresh failed: {}", e));
+        }
+        Err(e) => return Err(e),
+    }
+    Ok(resolved)
+}
+
+/// Resolve the profile's credentials and refresh them if they are about to expire
+pub async fn read_fresh_credentials(
+    profile: &Profile,
+) -> Result<ResolvedCredentials, CredentialError> {
+    let resolved = resolve_credentials(profile).await.map_err(|e| e.error)?;
+    refresh_if_needed(resolved).await
+}
+
+/// Authentication state of a profile, refreshing its token if it is about to expire
+pub async fn auth_status(profile: &Profile) -> AuthStatus {
+    let default_path = get_credentials_path()
+        .map(|path| path.display().to_string())
+        .unwrap_or_default();
+
+    let resolved = match resolve_credentials(profile).await {
+        Ok(resolved) => resolved,
+        Err(chain) => {
+            return AuthStatus {
+                profile_id: profile.id.clone(),
+                authenticated: false,
+                expires_at: None,
+                permission_warning: audit_credentials_permissions(Path::new(&default_path)),
+                clock_skew_seconds: clock::significant_skew_secs(),
+                missing_scopes: Vec::new(),
+                credentials_path: default_path,
+                error_reason: Some(chain.error.reason().to_string()),
+                source: None,
+                skipped_providers: chain.skipped,
+            }
+        }
+    };
+
+    let source = resolved.provider.name().to_string();
+    let skipped = resolved.skipped.clone();
+    let credentials_path = if matches!(source.as_str(), "file" | "default_file") {
+        resolved.provider.location()
+    } else {
+        default_path
+    };
+    let permission_warning = audit_credentials_permissions(Path::new(&credentials_path));
+
+    match refresh_if_needed(resolved).await {
+        Ok(resolved) => {
+            let is_valid = is_token_valid(&resolved.oauth);
+            let missing_scopes = missing_scopes(&resolved.oauth);
+            let error_reason = if !is_valid {
+                Some("token_expired")
+            } else if !missing_scopes.is_empty() {
+                Some("insufficient_scope")
+            } else {
+                None
+            };
+            AuthStatus {
+                profile_id: profile.id.clone(),
+                authenticated: error_reason.is_none(),
+                expires_at: Some(resolved.oauth.expires_at).filter(|at| *at != NO_EXPIRY),
+                credentials_path,
+                error_reason: error_reason.map(str::to_string),
+                source: Some(source),
+                skipped_providers: skipped,
+                permission_warning,
+                clock_skew_seconds: clock::significant_skew_secs(),
+                missing_scopes,
+            }
+        }
+        Err(e) => AuthStatus {
+            profile_id: profile.id.clone(),
+            authenticated: false,
+            expires_at: None,
+            credentials_path,
+            error_reason: Some(e.reason().to_string()),
+            source: Some(source),
+            skipped_providers: skipped,
+            permission_warning,
+            clock_skew_seconds: clock::significant_skew_secs(),
+            missing_scopes: Vec::new(),
+        },
+    }
+}
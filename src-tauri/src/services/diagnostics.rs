@@ -0,0 +1,60 @@
+use std::collections::HashSet;
+use std::io::Write;
+use std::path::PathBuf;
+use std::sync::{Mutex, OnceLock};
+
+use crate::services::app_dirs;
+
+/// Rotate the log once it grows past this size
+const MAX_LOG_BYTES: u64 = 256 * 1024;
+
+pub fn get_diagnostics_path() -> std::io::Result<PathBuf> {
+    Ok(app_dirs::data_dir()?.join("diagnostics.log"))
+}
+
+/// Log API schema differences, each distinct message once per run
+pub fn record_schema_drift(entries: &[String]) {
+    static SEEN: OnceLock<Mutex<HashSet<String>>> = OnceLock::new();
+    let Ok(mut seen) = SEEN.get_or_init(Default::default).lock() else {
+        return;
+    };
+
+    for entry in entries {
+        if seen.insert(entry.clone()) {
+            append("schema_drift", entry);
+        }
+    }
+}
+
+/// Log a background failure that has no caller to report it to
+pub fn record_error(category: &str, message: &str) {
+    append(category, message);
+}
+
+/// Diagnostics are best effort and never surface errors to the caller
+fn append(category: &str, message: &str) {
+    let Ok(path) = get_diagnostics_path() else {
+        return;
+    };
+    if let Some(parent) = path.parent() {
+        let _ = std::fs::create_dir_all(parent);
+    }
+
+    if std::fs::metadata(&path).is_ok_and(|meta| meta.len() > MAX_LOG_BYTES) {
+        let _ = std::fs::rename(&path, path.with_extension("log.old"));
+    }
+
+    if let Ok(mut file) = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&path)
+    {
+        let _ = writeln!(
+            file,
+            "{} [{}] {}",
+            chrono::Utc::now().to_rfc3339(),
+            category,
+            message
+        );
+    }
+}
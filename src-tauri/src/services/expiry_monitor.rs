@@ -0,0 +1,275 @@
+use std::collections::HashSet;
+use std::time::Duration;
+
+use serde::Serialize;
+use tauri::{AppHandle, Emitter, Manager};
+use tauri_plugin_notification::NotificationExt;
+
+use crate::services::settings_store::{self, Profile};
+use crate::services::usage_scheduler::{TokenStatus, UsageState};
+use crate::services::{
+    clock, diagnostics, refresh_credentials, resolve_credentials, CredentialError, NO_EXPIRY,
+};
+
+pub const AUTH_EXPIRING_EVENT: &str = "auth-expiring";
+
+const CHECK_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Payload of the `auth-expiring` event
+#[derive(Debug, Clone, Serialize)]
+pub struct ExpiryWarning {
+    pub profile_id: String,
+    pub profile_name: String,
+    pub expires_at: i64,
+    pub minutes_left: i64,
+    /// The configured threshold that was crossed
+    pub threshold_minutes: u32,
+    pub clock_skew_seconds: Option<i64>,
+}
+
+/// The most urgent threshold the token has crossed
+fn crossed_threshold(token: &TokenStatus, thresholds: &[u32], now: i64) -> Option<u32> {
+    if token.expires_at == NO_EXPIRY {
+        return None;
+    }
+    let remaining = token.expires_at - now;
+    if remaining <= 0 {
+        return None;
+    }
+    thresholds
+        .iter()
+        .copied()
+        .filter(|minutes| remaining <= i64::from(*minutes) * 60_000)
+        .min()
+}
+
+/// The threshold to warn at, if a warning is due. A renewable token is
+/// first renewed with `renew`, and only warned about when that fails, which
+/// also marks it no longer renewable. Tokens that came from such a renewal
+/// (`renewed_early`) are left to the usual refresh.
+fn warning_due(
+    token: &mut TokenStatus,
+    thresholds: &[u32],
+    now: i64,
+    renewed_early: bool,
+    renew: impl FnOnce() -> Option<TokenStatus>,
+) -> Option<u32> {
+    let threshold = crossed_threshold(token, thresholds, now)?;
+    if !token.renewable {
+        return Some(threshold);
+    }
+    if renewed_early {
+        return None;
+    }
+    match renew() {
+        Some(renewed) => {
+            *token = renewed;
+            None
+        }
+        None => {
+            token.renewable = false;
+            Some(threshold)
+        }
+    }
+}
+
+/// Refresh the profile's token ahead of expiry, so a refresh token revoked
+/// or rotated elsewhere is found while there is still time to log in again.
+/// A token already replaced since `expires_at` is returned as it is.
+pub async fn renew_before_expiry(
+    profile: &Profile,
+    expires_at: i64,
+) -> Result<TokenStatus, CredentialError> {
+    let resolved = resolve_credentials(profile).await.map_err(|e| e.error)?;
+    if resolved.oauth.expires_at != expires_at {
+        return Ok(TokenStatus::of(&resolved));
+    }
+    let oauth = refresh_credentials(&resolved).await?;
+    Ok(TokenStatus {
+        expires_at: oauth.expires_at,
+        ..TokenStatus::of(&resolved)
+    })
+}
+
+fn notify_desktop(app: &AppHandle, warning: &ExpiryWarning) {
+    let body = format!(
+        "{}: the Claude token expires in {} min. Run `claude` to log in again.",
+        warning.profile_name,
+        warning.minutes_left.max(1)
+    );
+    let _ = app
+        .notification()
+        .builder()
+        .title("Claude login expiring")
+        .body(body)
+        .show();
+}
+
+/// What the check loop remembers between runs
+#[derive(Default)]
+struct Seen {
+    /// Warnings shown, once per profile, token and threshold
+    announced: HashSet<(String, i64, u32)>,
+    /// Tokens obtained by renewing ahead of a threshold, by profile and expiry
+    renewed: HashSet<(String, i64)>,
+}
+
+/// Check every profile's token, as last seen by the usage scheduler,
+/// renewing renewable ones ahead of the thresholds and warning once per
+/// threshold per token otherwise
+fn check(app: &AppHandle, seen: &mut Seen) {
+    let settings = settings_store::load_settings_or_default();
+    let thresholds = &settings.expiry_warnings.minutes_before;
+    let now = chrono::Utc::now().timestamp_millis();
+    let state = app.state::<UsageState>();
+
+    for profile in settings.effective_profiles() {
+        let Some(mut token) = state.token(&profile.id) else {
+            continue;
+        };
+        let before = token;
+        let renewed_early = seen
+            .renewed
+            .contains(&(profile.id.clone(), token.expires_at));
+        let renew = || {
+            // On a blocking thread, so waiting here doesn't stall the runtime
+            tauri::async_runtime::block_on(renew_before_expiry(&profile, before.expires_at))
+                .map_err(|e| {
+                    diagnostics::record_error(
+                        "auth",
+                        &format!("{}: early token refresh failed: {}", profile.id, e),
+                    )
+                })
+                .ok()
+        };
+        let due = warning_due(&mut token, thresholds, now, renewed_early, renew);
+        if token != before {
+            if token.expires_at != before.expires_at {
+                seen.renewed.insert((profile.id.clone(), token.expires_at));
+            }
+            state.set_token(&profile.id, Some(token));
+        }
+        let Some(threshold) = due else {
+            continue;
+        };
+        if !seen
+            .announced
+            .insert((profile.id.clone(), token.expires_at, threshold))
+        {
+            continue;
+        }
+
+        let warning = ExpiryWarning {
+            profile_id: profile.id.clone(),
+            profile_name: profile.name.clone(),
+            expires_at: token.expires_at,
+            minutes_left: (token.expires_at - now) / 60_000,
+            threshold_minutes: threshold,
+            clock_skew_seconds: clock::significant_skew_secs(),
+        };
+        let _ = app.emit(AUTH_EXPIRING_EVENT, &warning);
+        if settings.expiry_warnings.desktop_notifications {
+            notify_desktop(app, &warning);
+        }
+    }
+}
+
+/// Spawn the expiry check loop. Requires `UsageState` to be managed.
+pub fn start(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        let mut seen = Seen::default();
+        loop {
+            // Reads settings, refreshes tokens and shows notifications, so
+            // off the async runtime
+            let handle = app.clone();
+            // A panicked check loses what it has seen; warnings may repeat
+            seen = tauri::async_runtime::spawn_blocking(move || {
+                check(&handle, &mut seen);
+                seen
+            })
+            .await
+            .unwrap_or_default();
+            tokio::time::sleep(CHECK_INTERVAL).await;
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MINUTE_MS: i64 = 60_000;
+
+    fn token(minutes_left: i64, renewable: bool) -> TokenStatus {
+        TokenStatus {
+            expires_at: 1_000 * MINUTE_MS + minutes_left * MINUTE_MS,
+            renewable,
+        }
+    }
+
+    #[test]
+    fn warns_at_the_most_urgent_threshold_crossed() {
+        let now = 1_000 * MINUTE_MS;
+        let thresholds = [30, 5];
+
+        assert_eq!(crossed_threshold(&token(45, false), &thresholds, now), None);
+        assert_eq!(
+            crossed_threshold(&token(29, false), &thresholds, now),
+            Some(30)
+        );
+        assert_eq!(
+            crossed_threshold(&token(4, false), &thresholds, now),
+            Some(5)
+        );
+        assert_eq!(crossed_threshold(&token(-1, false), &thresholds, now), None);
+    }
+
+    #[test]
+    fn renews_a_renewable_token_instead_of_warning() {
+        let now = 1_000 * MINUTE_MS;
+        let mut renewable = token(29, true);
+        let renewed = token(8 * 60, true);
+
+        assert_eq!(
+            warning_due(&mut renewable, &[30, 5], now, false, || Some(renewed)),
+            None
+        );
+        assert_eq!(renewable, renewed);
+    }
+
+    #[test]
+    fn warns_when_renewing_before_expiry_fails() {
+        let now = 1_000 * MINUTE_MS;
+        let mut renewable = token(29, true);
+
+        assert_eq!(
+            warning_due(&mut renewable, &[30, 5], now, false, || None),
+            Some(30)
+        );
+        assert!(!renewable.renewable);
+        // Later checks warn without trying again
+        assert_eq!(
+            warning_due(
+                &mut renewable,
+                &[30, 5],
+                now + 25 * MINUTE_MS,
+                false,
+                || { panic!("renewed again") }
+            ),
+            Some(5)
+        );
+    }
+
+    #[test]
+    fn leaves_an_early_renewal_to_the_usual_refresh() {
+        let now = 1_000 * MINUTE_MS;
+        let mut renewed = token(20, true);
+
+        assert_eq!(
+            warning_due(&mut renewed, &[30, 5], now, true, || panic!(
+                "renewed again"
+            )),
+            None
+        );
+    }
+}
@@ -0,0 +1,272 @@
+//! `claude` login running in a pseudo-terminal owned by the app, so it works
+//! without an external terminal emulator or window-title matching
+
+use std::io::{Read, Write};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, PoisonError};
+use std::time::Duration;
+
+use portable_pty::{native_pty_system, ChildKiller, CommandBuilder, MasterPty, PtySize};
+use serde::Serialize;
+use tauri::{AppHandle, Emitter, EventId, Listener, Manager};
+use thiserror::Error;
+
+use crate::services::auth_watcher::AUTH_CHANGED_EVENT;
+use crate::services::settings_store::Profile;
+use crate::services::{auth_status, claude_cli};
+
+pub const OUTPUT_EVENT: &str = "login-terminal-output";
+pub const EXIT_EVENT: &str = "login-terminal-exit";
+
+/// Time `claude` gets to exit after SIGHUP before its process group is killed
+const KILL_GRACE: Duration = Duration::from_secs(2);
+
+#[derive(Error, Debug)]
+pub enum LoginTerminalError {
+    #[error("Failed to open a pseudo-terminal: {0}")]
+    Pty(String),
+
+    #[error("Failed to start claude: {0}")]
+    Spawn(String),
+
+    #[error("No login terminal is running")]
+    NotRunning,
+
+    #[error("Login terminal I/O failed: {0}")]
+    Io(#[from] std::io::Error),
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct LoginTerminalOutput {
+    pub session_id: u64,
+    pub data: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct LoginTerminalExit {
+    pub session_id: u64,
+    pub exit_code: Option<u32>,
+    pub authenticated: bool,
+}
+
+struct Session {
+    id: u64,
+    master: Box<dyn MasterPty + Send>,
+    writer: Box<dyn Write + Send>,
+    killer: Box<dyn ChildKiller + Send + Sync>,
+    pid: Option<u32>,
+    exited: Arc<AtomicBool>,
+    auth_listener: EventId,
+}
+
+/// The running login session, if any. Only one exists at a time.
+#[derive(Default)]
+pub struct LoginTerminal {
+    session: Mutex<Option<Session>>,
+    next_id: AtomicU64,
+}
+
+impl LoginTerminal {
+    fn lock(&self) -> std::sync::MutexGuard<'_, Option<Session>> {
+        self.session.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Start `claude` for `profile` in a `cols`×`rows` terminal. Output is
+    /// emitted as `login-terminal-output`; once the profile's credentials
+    /// change to a valid login the child is stopped.
+    pub async fn start(
+        &self,
+        app: &AppHandle,
+        profile: Profile,
+        cols: u16,
+        rows: u16,
+    ) -> Result<u64, LoginTerminalError> {
+        self.stop(app, None);
+
+        let initial_expiry = auth_status(&profile).await.expires_at;
+        let pair = native_pty_system()
+            .openpty(PtySize {
+                rows,
+                cols,
+                pixel_width: 0,
+                pixel_height: 0,
+            })
+            .map_err(|e| LoginTerminalError::Pty(e.to_string()))?;
+
+        let mut cmd = CommandBuilder::new(claude_cli::command());
+        cmd.env("TERM", "xterm-256color");
+        if let Some(home) = dirs::home_dir() {
+            cmd.cwd(home);
+        }
+        let mut child = pair
+            .slave
+            .spawn_command(cmd)
+            .map_err(|e| LoginTerminalError::Spawn(e.to_string()))?;
+        // Only the child keeps the slave open, so reads end when it exits
+        drop(pair.slave);
+
+        let reader = pair
+            .master
+            .try_clone_reader()
+            .map_err(|e| LoginTerminalError::Pty(e.to_string()))?;
+        let writer = pair
+            .master
+            .take_writer()
+            .map_err(|e| LoginTerminalError::Pty(e.to_string()))?;
+
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed) + 1;
+        let exited = Arc::new(AtomicBool::new(false));
+
+        // The auth watcher already follows the credentials file; a change to a
+        // valid login for this profile means `claude` has done its job
+        let handle = app.clone();
+        let profile_id = profile.id.clone();
+        let auth_listener = app.listen_any(AUTH_CHANGED_EVENT, move |event| {
+            let Ok(status) = serde_json::from_str::<serde_json::Value>(event.payload()) else {
+                return;
+            };
+            let logged_in = status["profile_id"] == profile_id.as_str()
+                && status["authenticated"] == true
+                && status["expires_at"].as_i64() != initial_expiry;
+            if logged_in {
+                handle.state::<LoginTerminal>().stop(&handle, Some(id));
+            }
+        });
+
+        *self.lock() = Some(Session {
+            id,
+            master: pair.master,
+            writer,
+            killer: child.clone_killer(),
+            pid: child.process_id(),
+            exited: exited.clone(),
+            auth_listener,
+        });
+
+        let handle = app.clone();
+        std::thread::spawn(move || stream_output(&handle, id, reader));
+
+        let handle = app.clone();
+        std::thread::spawn(move || {
+            let exit_code = child.wait().ok().map(|status| status.exit_code());
+            exited.store(true, Ordering::Relaxed);
+
+            let terminal = handle.state::<LoginTerminal>();
+            terminal.stop(&handle, Some(id));
+            let authenticated = tauri::async_runtime::block_on(auth_status(&profile)).authenticated;
+            let _ = handle.emit(
+                EXIT_EVENT,
+                LoginTerminalExit {
+                    session_id: id,
+                    exit_code,
+                    authenticated,
+                },
+            );
+        });
+
+        Ok(id)
+    }
+
+    /// Forward keystrokes from the webview to the child
+    pub fn write(&self, data: &str) -> Result<(), LoginTerminalError> {
+        let mut session = self.lock();
+        let session = session.as_mut().ok_or(LoginTerminalError::NotRunning)?;
+        session.writer.write_all(data.as_bytes())?;
+        session.writer.flush()?;
+        Ok(())
+    }
+
+    pub fn resize(&self, cols: u16, rows: u16) -> Result<(), LoginTerminalError> {
+        let session = self.lock();
+        let session = session.as_ref().ok_or(LoginTerminalError::NotRunning)?;
+        session
+            .master
+            .resize(PtySize {
+                rows,
+                cols,
+                pixel_width: 0,
+                pixel_height: 0,
+            })
+            .map_err(|e| LoginTerminalError::Pty(e.to_string()))
+    }
+
+    /// Hang up on the session (`id`, or whichever is running when `None`)
+    /// and kill its process group if it is still around after `KILL_GRACE`
+    pub fn stop(&self, app: &AppHandle, id: Option<u64>) {
+        let session = {
+            let mut current = self.lock();
+            match current.as_ref() {
+                Some(session) if id.is_none_or(|id| id == session.id) => current.take(),
+                _ => None,
+            }
+        };
+        let Some(mut session) = session else {
+            return;
+        };
+
+        app.unlisten(session.auth_listener);
+        if session.exited.load(Ordering::Relaxed) {
+            return;
+        }
+        let _ = session.killer.kill();
+
+        std::thread::spawn(move || {
+            std::thread::sleep(KILL_GRACE);
+            if !session.exited.load(Ordering::Relaxed) {
+                force_kill(&mut session);
+            }
+        });
+    }
+}
+
+#[cfg(unix)]
+fn force_kill(session: &mut Session) {
+    // The child leads its own session, so its pid is also its process group
+    let Some(pgid) = session
+        .pid
+        .and_then(|pid| libc::pid_t::try_from(pid).ok())
+        .filter(|pgid| *pgid > 0)
+    else {
+        return;
+    };
+    // SAFETY: killpg has no memory preconditions, and a positive pgid only
+    // ever names the child's group, never our own (0) or every process (-1)
+    unsafe {
+        libc::killpg(pgid, libc::SIGKILL);
+    }
+}
+
+#[cfg(not(unix))]
+fn force_kill(session: &mut Session) {
+    let _ = session.killer.kill();
+}
+
+/// Longest valid UTF-8 prefix of `pending`, leaving a split character for
+/// the next read. Invalid bytes are replaced rather than held back forever.
+fn take_utf8(pending: &mut Vec<u8>) -> String {
+    let valid = match std::str::from_utf8(pending) {
+        Err(e) if e.error_len().is_none() => e.valid_up_to(),
+        _ => pending.len(),
+    };
+    let text = String::from_utf8_lossy(&pending[..valid]).into_owned();
+    pending.drain(..valid);
+    text
+}
+
+fn stream_output(app: &AppHandle, session_id: u64, mut reader: Box<dyn Read + Send>) {
+    let mut buf = [0u8; 4096];
+    let mut pending = Vec::new();
+
+    loop {
+        match reader.read(&mut buf) {
+            Ok(0) | Err(_) => return,
+            Ok(n) => {
+                pending.extend_from_slice(&buf[..n]);
+                let data = take_utf8(&mut pending);
+                if !data.is_empty() {
+                    let _ = app.emit(OUTPUT_EVENT, LoginTerminalOutput { session_id, data });
+                }
+            }
+        }
+    }
+}
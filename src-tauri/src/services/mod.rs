@@ -1,6 +1,26 @@
+pub mod accelerator;
 mod anthropic_api;
+pub mod app_dirs;
+pub mod auth_watcher;
+pub mod claude_cli;
+pub mod clock;
+pub mod credential_provider;
 mod credential_store;
+pub mod diagnostics;
+pub mod expiry_monitor;
+pub mod login_terminal;
+pub mod network;
+pub mod oauth_login;
+pub mod redact;
+#[cfg(target_os = "linux")]
+mod secret_service;
+pub mod settings_migrations;
 pub mod settings_store;
+pub mod shortcut_manager;
+mod subprocess;
+pub mod terminal_launcher;
+pub mod usage_cache;
+pub mod usage_scheduler;
 
 pub use anthropic_api::*;
 pub use credential_store::*;
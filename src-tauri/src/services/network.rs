@@ -0,0 +1,363 @@
+use std::error::Error as StdError;
+use std::time::Duration;
+
+use serde::Serialize;
+use thiserror::Error;
+
+use crate::services::settings_store::{self, NetworkConfig};
+
+const PROXY_ENV: &str = "CLAUDE_USAGE_MONITOR_PROXY";
+const NO_PROXY_ENV: &str = "CLAUDE_USAGE_MONITOR_NO_PROXY";
+/// List of PEM files, separated like `PATH`
+const CA_CERTS_ENV: &str = "CLAUDE_USAGE_MONITOR_CA_CERTS";
+
+#[derive(Error, Debug)]
+pub enum NetworkError {
+    #[error("Invalid proxy URL: {0}")]
+    InvalidProxy(String),
+
+    #[error("Failed to read CA certificate {path}: {message}")]
+    InvalidCertificate { path: String, message: String },
+
+    #[error("Failed to build HTTP client: {0}")]
+    Client(#[from] reqwest::Error),
+}
+
+/// Network settings with the environment variable overrides applied
+pub fn effective_network_config() -> NetworkConfig {
+    let mut config = settings_store::load_settings_or_default().network;
+
+    if let Some(proxy) = std::env::var(PROXY_ENV)
+        .ok()
+        .filter(|v| !v.trim().is_empty())
+    {
+        config.proxy_url = Some(proxy);
+    }
+    if let Ok(no_proxy) = std::env::var(NO_PROXY_ENV) {
+        config.no_proxy = Some(no_proxy);
+    }
+    if let Some(paths) = std::env::var_os(CA_CERTS_ENV) {
+        config.ca_cert_paths = std::env::split_paths(&paths)
+            .map(|path| path.display().to_string())
+            .filter(|path| !path.is_empty())
+            .collect();
+    }
+
+    config
+}
+
+/// Client builder with the proxy and extra CA roots from `config` applied.
+/// Without an explicit proxy, reqwest still honors the system `*_PROXY` variables.
+pub fn configure_client(
+    mut builder: reqwest::ClientBuilder,
+    config: &NetworkConfig,
+) -> Result<reqwest::ClientBuilder, NetworkError> {
+    if let Some(proxy_url) = config.proxy_url.as_deref().map(str::trim) {
+        if !proxy_url.is_empty() {
+            let mut proxy = reqwest::Proxy::all(proxy_url)
+                .map_err(|_| NetworkError::InvalidProxy(redact_url(proxy_url)))?;
+            if let Some(no_proxy) = config.no_proxy.as_deref() {
+                proxy = proxy.no_proxy(reqwest::NoProxy::from_string(no_proxy));
+            }
+            builder = builder.proxy(proxy);
+        }
+    }
+
+    for path in &config.ca_cert_paths {
+        let invalid = |message: String| NetworkError::InvalidCertificate {
+            path: path.clone(),
+            message,
+        };
+        let pem = std::fs::read(path).map_err(|e| invalid(e.to_string()))?;
+        let certs =
+            reqwest::Certificate::from_pem_bundle(&pem).map_err(|e| invalid(e.to_string()))?;
+        if certs.is_empty() {
+            return Err(invalid("no certificates found".to_string()));
+        }
+        for cert in certs {
+            builder = builder.add_root_certificate(cert);
+        }
+    }
+
+    Ok(builder)
+}
+
+/// Drop `user:password@` so proxy credentials never reach the UI
+pub fn redact_url(url: &str) -> String {
+    match reqwest::Url::parse(url) {
+        Ok(mut parsed) if !parsed.username().is_empty() || parsed.password().is_some() => {
+            let _ = parsed.set_username("");
+            let _ = parsed.set_password(None);
+            parsed.to_string()
+        }
+        Ok(parsed) => parsed.to_string(),
+        Err(_) => "<invalid url>".to_string(),
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CheckStatus {
+    Ok,
+    Failed,
+    Skipped,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ConnectionCheck {
+    /// `config`, `dns`, `proxy`, `tls` or `http`
+    pub stage: &'static str,
+    pub status: CheckStatus,
+    pub detail: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ConnectionReport {
+    pub target: String,
+    pub proxy: Option<String>,
+    pub ok: bool,
+    pub checks: Vec<ConnectionCheck>,
+}
+
+impl ConnectionReport {
+    fn push(&mut self, stage: &'static str, status: CheckStatus, detail: impl Into<String>) {
+        if status == CheckStatus::Failed {
+            self.ok = false;
+        }
+        self.checks.push(ConnectionCheck {
+            stage,
+            status,
+            detail: detail.into(),
+        });
+    }
+
+    /// Mark every stage that never ran because an earlier one failed
+    fn skip_remaining(&mut self, stages: &[&'static str]) {
+        for stage in stages {
+            self.push(stage, CheckStatus::Skipped, "not reached");
+        }
+    }
+}
+
+/// `error` and everything it wraps, including the error inside an
+/// `io::Error`, which `source()` skips
+fn error_chain<'a>(
+    error: &'a (dyn StdError + 'static),
+) -> impl Iterator<Item = &'a (dyn StdError + 'static)> {
+    std::iter::successors(Some(error), |&err| {
+        match err
+            .downcast_ref::<std::io::Error>()
+            .and_then(|io| io.get_ref())
+        {
+            Some(inner) => Some(inner as &(dyn StdError + 'static)),
+            None => err.source(),
+        }
+    })
+}
+
+/// The handshake failed, or the server's certificate was rejected
+fn is_tls_error(error: &(dyn StdError + 'static)) -> bool {
+    error_chain(error).any(|err| err.is::<rustls::Error>())
+}
+
+/// Which stage a failed request broke at. Proxy errors have no public type,
+/// but through a proxy any other failure to connect happens at the proxy.
+fn classify_request_error(error: &reqwest::Error, via_proxy: bool) -> &'static str {
+    if is_tls_error(error) {
+        "tls"
+    } else if via_proxy && error.is_connect() {
+        "proxy"
+    } else {
+        "http"
+    }
+}
+
+/// Resolve the first host we must reach: the proxy if one is set, else the target
+async fn check_dns(report: &mut ConnectionReport, url: &reqwest::Url) -> bool {
+    let Some(host) = url.host_str() else {
+        report.push("dns", CheckStatus::Failed, "URL has no host");
+        return false;
+    };
+    let port = url.port_or_known_default().unwrap_or(443);
+
+    match tokio::net::lookup_host((host, port)).await {
+        Ok(mut addrs) => match addrs.next() {
+            Some(addr) => {
+                report.push("dns", CheckStatus::Ok, format!("{} -> {}", host, addr.ip()));
+                true
+            }
+            None => {
+                report.push(
+                    "dns",
+                    CheckStatus::Failed,
+                    format!("{} has no addresses", host),
+                );
+                false
+            }
+        },
+        Err(e) => {
+            report.push("dns", CheckStatus::Failed, format!("{}: {}", host, e));
+            false
+        }
+    }
+}
+
+/// Diagnose connectivity to `target` step by step: config, DNS, proxy, TLS, HTTP
+pub async fn test_connection(target: &str) -> ConnectionReport {
+    let config = effective_network_config();
+    let proxy = config
+        .proxy_url
+        .as_deref()
+        .map(str::trim)
+        .filter(|url| !url.is_empty());
+
+    let mut report = ConnectionReport {
+        target: target.to_string(),
+        proxy: proxy.map(redact_url),
+        ok: true,
+        checks: Vec::new(),
+    };
+
+    let Ok(target_url) = reqwest::Url::parse(target) else {
+        report.push(
+            "config",
+            CheckStatus::Failed,
+            format!("invalid URL: {}", target),
+        );
+        report.skip_remaining(&["dns", "proxy", "tls", "http"]);
+        return report;
+    };
+    // Plain HTTP never gets to a handshake
+    let uses_tls = target_url.scheme() == "https";
+
+    let client = match configure_client(
+        reqwest::Client::builder().connect_timeout(Duration::from_secs(10)),
+        &config,
+    )
+    .and_then(|builder| {
+        builder
+            .timeout(Duration::from_secs(20))
+            .build()
+            .map_err(Into::into)
+    }) {
+        Ok(client) => {
+            report.push("config", CheckStatus::Ok, "proxy and certificates loaded");
+            client
+        }
+        Err(e) => {
+            report.push("config", CheckStatus::Failed, e.to_string());
+            report.skip_remaining(&["dns", "proxy", "tls", "http"]);
+            return report;
+        }
+    };
+
+    let dns_url = match proxy.map(reqwest::Url::parse) {
+        Some(Ok(proxy_url)) => proxy_url,
+        _ => target_url.clone(),
+    };
+
+    if !check_dns(&mut report, &dns_url).await {
+        report.skip_remaining(&["proxy", "tls", "http"]);
+        return report;
+    }
+
+    let proxy_passed = |report: &mut ConnectionReport| match proxy {
+        Some(_) => report.push("proxy", CheckStatus::Ok, "connected through proxy"),
+        None => report.push("proxy", CheckStatus::Skipped, "no proxy configured"),
+    };
+    let tls_passed = |report: &mut ConnectionReport| {
+        if uses_tls {
+            report.push("tls", CheckStatus::Ok, "certificate accepted");
+        } else {
+            report.push("tls", CheckStatus::Skipped, "not an https URL");
+        }
+    };
+
+    match client.get(target_url).send().await {
+        Ok(response) if response.status() == reqwest::StatusCode::PROXY_AUTHENTICATION_REQUIRED => {
+            report.push(
+                "proxy",
+                CheckStatus::Failed,
+                "proxy authentication required (407)",
+            );
+            report.skip_remaining(&["tls", "http"]);
+        }
+        Ok(response) => {
+            proxy_passed(&mut report);
+            tls_passed(&mut report);
+            // Any HTTP answer, even 401/404, proves the endpoint is reachable
+            report.push(
+                "http",
+                CheckStatus::Ok,
+                format!("HTTP {}", response.status().as_u16()),
+            );
+        }
+        Err(e) => match classify_request_error(&e, proxy.is_some()) {
+            "tls" => {
+                proxy_passed(&mut report);
+                report.push("tls", CheckStatus::Failed, e.to_string());
+                report.skip_remaining(&["http"]);
+            }
+            "proxy" => {
+                report.push("proxy", CheckStatus::Failed, e.to_string());
+                report.skip_remaining(&["tls", "http"]);
+            }
+            _ => {
+                proxy_passed(&mut report);
+                if uses_tls && !e.is_connect() {
+                    tls_passed(&mut report);
+                } else {
+                    report.push("tls", CheckStatus::Skipped, "not reached");
+                }
+                report.push("http", CheckStatus::Failed, e.to_string());
+            }
+        },
+    }
+
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn finds_tls_errors_wrapped_in_io_errors() {
+        let rejected = rustls::Error::InvalidCertificate(rustls::CertificateError::UnknownIssuer);
+        let error = std::io::Error::new(std::io::ErrorKind::InvalidData, rejected);
+
+        assert!(is_tls_error(&error));
+    }
+
+    #[test]
+    fn ignores_errors_that_only_mention_tls() {
+        let error = std::io::Error::new(
+            std::io::ErrorKind::ConnectionRefused,
+            "tls certificate proxy 407",
+        );
+
+        assert!(!is_tls_error(&error));
+    }
+
+    #[tokio::test]
+    async fn skips_every_stage_after_an_invalid_url() {
+        let report = test_connection("not a url").await;
+
+        let stages: Vec<_> = report
+            .checks
+            .iter()
+            .map(|check| (check.stage, check.status))
+            .collect();
+        assert_eq!(
+            stages,
+            vec![
+                ("config", CheckStatus::Failed),
+                ("dns", CheckStatus::Skipped),
+                ("proxy", CheckStatus::Skipped),
+                ("tls", CheckStatus::Skipped),
+                ("http", CheckStatus::Skipped),
+            ]
+        );
+        assert!(!report.ok);
+    }
+}
@@ -0,0 +1,255 @@
+//! In-app OAuth login: authorization code flow with PKCE and a loopback redirect
+
+use std::time::Duration;
+
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use reqwest::Url;
+use sha2::{Digest, Sha256};
+use thiserror::Error;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+
+use crate::models::{ClaudeOAuth, Secret};
+use crate::services::anthropic_api::{self, ApiError, AuthorizationCodeGrant, OAUTH_CLIENT_ID};
+use crate::services::credential_provider::build_provider;
+use crate::services::credential_store::{oauth_from_token_response, CredentialError};
+use crate::services::settings_store::Profile;
+
+/// How long to wait for the browser to come back before giving up
+pub const LOGIN_TIMEOUT: Duration = Duration::from_secs(5 * 60);
+
+const CALLBACK_PATH: &str = "/callback";
+/// Same scopes the Claude Code CLI asks for
+const LOGIN_SCOPES: &str = "org:create_api_key user:profile user:inference";
+/// Longest request head accepted on the loopback listener
+const MAX_REQUEST_HEAD: usize = 16 * 1024;
+/// How long a connection to the loopback listener gets to send its request
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+const SUCCESS_PAGE: &str = "<!doctype html><html><body style=\"font-family: sans-serif\">\
+<h2>Logged in</h2><p>You can close this window and return to Claude Usage Monitor.</p>\
+</body></html>";
+const FAILURE_PAGE: &str = "<!doctype html><html><body style=\"font-family: sans-serif\">\
+<h2>Login failed</h2><p>Return to Claude Usage Monitor to try again.</p>\
+</body></html>";
+
+#[derive(Error, Debug)]
+pub enum LoginError {
+    #[error("Could not listen for the login redirect: {0}")]
+    Listener(#[from] std::io::Error),
+
+    #[error("No secure random source: {0}")]
+    Random(String),
+
+    #[error("Invalid authorization endpoint: {0}")]
+    InvalidEndpoint(String),
+
+    #[error("Could not open the browser: {0}")]
+    Browser(String),
+
+    #[error("Login timed out; no response from the browser")]
+    Timeout,
+
+    #[error("Authorization was denied: {0}")]
+    Denied(String),
+
+    #[error("Failed to exchange the authorization code: {0}")]
+    Exchange(#[from] ApiError),
+
+    #[error("None of this profile's credential providers can store tokens")]
+    NoStorage,
+
+    #[error("Failed to store credentials: {0}")]
+    Store(#[from] CredentialError),
+}
+
+/// PKCE verifier and its S256 challenge
+struct Pkce {
+    verifier: Secret,
+    challenge: String,
+}
+
+impl Pkce {
+    fn generate() -> Result<Self, LoginError> {
+        let verifier = random_url_safe(32)?;
+        let challenge = URL_SAFE_NO_PAD.encode(Sha256::digest(verifier.as_bytes()));
+        Ok(Self {
+            verifier: Secret::new(verifier),
+            challenge,
+        })
+    }
+}
+
+fn random_url_safe(len: usize) -> Result<String, LoginError> {
+    let mut bytes = vec![0u8; len];
+    getrandom::getrandom(&mut bytes).map_err(|e| LoginError::Random(e.to_string()))?;
+    Ok(URL_SAFE_NO_PAD.encode(bytes))
+}
+
+fn authorize_url(redirect_uri: &str, challenge: &str, state: &str) -> Result<String, LoginError> {
+    let endpoint = anthropic_api::authorize_endpoint();
+    let mut url = Url::parse(&endpoint)
+        .map_err(|e| LoginError::InvalidEndpoint(format!("{}: {}", endpoint, e)))?;
+    url.query_pairs_mut()
+        .append_pair("code", "true")
+        .append_pair("client_id", OAUTH_CLIENT_ID)
+        .append_pair("response_type", "code")
+        .append_pair("redirect_uri", redirect_uri)
+        .append_pair("scope", LOGIN_SCOPES)
+        .append_pair("code_challenge", challenge)
+        .append_pair("code_challenge_method", "S256")
+        .append_pair("state", state);
+    Ok(url.into())
+}
+
+/// Read a request head and return its target, e.g. `/callback?code=…`
+async fn read_request_target(stream: &mut TcpStream) -> std::io::Result<Option<String>> {
+    let mut buf = Vec::new();
+    let mut chunk = [0u8; 2048];
+
+    while !buf.windows(4).any(|w| w == b"\r\n\r\n") {
+        if buf.len() > MAX_REQUEST_HEAD {
+            return Ok(None);
+        }
+        let n = stream.read(&mut chunk).await?;
+        if n == 0 {
+            return Ok(None);
+        }
+        buf.extend_from_slice(&chunk[..n]);
+    }
+
+    let head = String::from_utf8_lossy(&buf);
+    let mut request_line = head.lines().next().unwrap_or_default().split_whitespace();
+    Ok(match (request_line.next(), request_line.next()) {
+        (Some("GET"), Some(target)) => Some(target.to_string()),
+        _ => None,
+    })
+}
+
+async fn respond(stream: &mut TcpStream, status: &str, body: &str) {
+    let response = format!(
+        "HTTP/1.1 {}\r\nContent-Type: text/html; charset=utf-8\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        body.len(),
+        body
+    );
+    // The browser tab is cosmetic; the login result doesn't depend on it
+    let _ = stream.write_all(response.as_bytes()).await;
+    let _ = stream.shutdown().await;
+}
+
+/// Answer one connection to the loopback listener: `Some` with the login
+/// result for the redirect, `None` for anything else. Stray requests such as
+/// `/favicon.ico` get a 404; a redirect without this login's `state` gets a
+/// 400 and leaves the login waiting for the real one.
+async fn handle_connection(
+    mut stream: TcpStream,
+    state: &str,
+) -> Option<Result<Secret, LoginError>> {
+    // Browsers open spare connections they may never send anything on
+    let target = tokio::time::timeout(REQUEST_TIMEOUT, read_request_target(&mut stream))
+        .await
+        .ok()?
+        .ok()??;
+    let Ok(url) = Url::parse(&format!("http://localhost{}", target)) else {
+        respond(&mut stream, "400 Bad Request", FAILURE_PAGE).await;
+        return None;
+    };
+    if url.path() != CALLBACK_PATH {
+        respond(&mut stream, "404 Not Found", "").await;
+        return None;
+    }
+
+    let param = |name: &str| {
+        url.query_pairs()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.into_owned())
+    };
+
+    if param("state").as_deref() != Some(state) {
+        respond(&mut stream, "400 Bad Request", FAILURE_PAGE).await;
+        return None;
+    }
+    if let Some(error) = param("error") {
+        respond(&mut stream, "200 OK", FAILURE_PAGE).await;
+        return Some(Err(LoginError::Denied(
+            param("error_description").unwrap_or(error),
+        )));
+    }
+    let Some(code) = param("code") else {
+        respond(&mut stream, "400 Bad Request", FAILURE_PAGE).await;
+        return None;
+    };
+
+    respond(&mut stream, "200 OK", SUCCESS_PAGE).await;
+    Some(Ok(Secret::new(code)))
+}
+
+/// Accept connections until the browser is redirected back with a code.
+/// Each connection is handled on its own task, so one that stays idle
+/// doesn't hold up the others.
+async fn wait_for_code(listener: &TcpListener, state: &str) -> Result<Secret, LoginError> {
+    let (result_tx, mut result_rx) = tokio::sync::mpsc::channel(1);
+    loop {
+        tokio::select! {
+            accepted = listener.accept() => {
+                let (stream, _) = accepted?;
+                let result_tx = result_tx.clone();
+                let state = state.to_string();
+                tokio::spawn(async move {
+                    if let Some(result) = handle_connection(stream, &state).await {
+                        let _ = result_tx.send(result).await;
+                    }
+                });
+            }
+            Some(result) = result_rx.recv() => return result,
+        }
+    }
+}
+
+/// Log in through the browser and store the tokens with the first of the
+/// profile's providers that can store them. `open_browser` is handed the
+/// authorization URL; the redirect is caught on a loopback listener.
+pub async fn login<F>(profile: &Profile, open_browser: F) -> Result<ClaudeOAuth, LoginError>
+where
+    F: FnOnce(&str) -> Result<(), String>,
+{
+    let provider = profile
+        .credential_providers
+        .iter()
+        .map(build_provider)
+        .find(|provider| provider.can_store())
+        .ok_or(LoginError::NoStorage)?;
+
+    let listener = TcpListener::bind(("127.0.0.1", 0)).await?;
+    let redirect_uri = format!(
+        "http://localhost:{}{}",
+        listener.local_addr()?.port(),
+        CALLBACK_PATH
+    );
+    let pkce = Pkce::generate()?;
+    let state = random_url_safe(32)?;
+
+    open_browser(&authorize_url(&redirect_uri, &pkce.challenge, &state)?)
+        .map_err(LoginError::Browser)?;
+
+    let code = tokio::time::timeout(LOGIN_TIMEOUT, wait_for_code(&listener, &state))
+        .await
+        .map_err(|_| LoginError::Timeout)??;
+
+    let token = anthropic_api::exchange_authorization_code(&AuthorizationCodeGrant {
+        code: &code,
+        code_verifier: &pkce.verifier,
+        redirect_uri: &redirect_uri,
+        state: &state,
+    })
+    .await?;
+
+    let oauth = oauth_from_token_response(token, None);
+    let stored = oauth.clone();
+    tauri::async_runtime::spawn_blocking(move || provider.store(&stored))
+        .await
+        .map_err(|e| CredentialError::Unavailable(e.to_string()))??;
+    Ok(oauth)
+}
@@ -0,0 +1,137 @@
+//! Scrubbing of text that may carry credentials before it reaches the UI
+
+const REDACTED: &str = "[REDACTED]";
+
+/// Error bodies are cut to this many characters; the start explains the failure
+const MAX_ERROR_BODY_CHARS: usize = 500;
+
+/// Runs at least this long that mix letters and digits look like tokens
+const MIN_TOKEN_LEN: usize = 32;
+
+/// JSON keys whose values are always dropped
+const SENSITIVE_KEYS: &[&str] = &[
+    "token",
+    "secret",
+    "password",
+    "authorization",
+    "cookie",
+    "api_key",
+    "apikey",
+    "credential",
+];
+
+fn is_sensitive_key(key: &str) -> bool {
+    let key = key.to_ascii_lowercase();
+    SENSITIVE_KEYS
+        .iter()
+        .any(|sensitive| key.contains(sensitive))
+}
+
+fn scrub_json(value: &mut serde_json::Value) {
+    match value {
+        serde_json::Value::Object(fields) => {
+            for (key, value) in fields.iter_mut() {
+                if is_sensitive_key(key) && !value.is_null() {
+                    *value = REDACTED.into();
+                } else {
+                    scrub_json(value);
+                }
+            }
+        }
+        serde_json::Value::Array(items) => items.iter_mut().for_each(scrub_json),
+        serde_json::Value::String(text) => *text = scrub_words(text),
+        _ => {}
+    }
+}
+
+fn is_token_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.' | '+' | '/' | '=' | '~')
+}
+
+fn looks_like_token(word: &str) -> bool {
+    word.starts_with("sk-ant-")
+        || (word.len() >= MIN_TOKEN_LEN
+            && word.chars().any(|c| c.is_ascii_alphabetic())
+            && word.chars().any(|c| c.is_ascii_digit()))
+}
+
+fn push_word(output: &mut String, word: &mut String) {
+    if looks_like_token(word) {
+        output.push_str(REDACTED);
+    } else {
+        output.push_str(word);
+    }
+    word.clear();
+}
+
+/// Replace every token-looking word, keeping the text around it
+fn scrub_words(text: &str) -> String {
+    let mut output = String::with_capacity(text.len());
+    let mut word = String::new();
+
+    for c in text.chars() {
+        if is_token_char(c) {
+            word.push(c);
+        } else {
+            push_word(&mut output, &mut word);
+            output.push(c);
+        }
+    }
+    push_word(&mut output, &mut word);
+    output
+}
+
+/// Remove tokens and credential fields from free text or a JSON document
+pub fn scrub(text: &str) -> String {
+    match serde_json::from_str::<serde_json::Value>(text) {
+        Ok(mut value) if value.is_object() || value.is_array() => {
+            scrub_json(&mut value);
+            value.to_string()
+        }
+        _ => scrub_words(text),
+    }
+}
+
+/// Scrubbed and shortened server response body, safe to show and paste
+pub fn scrub_error_body(body: &str) -> String {
+    let scrubbed = scrub(body.trim());
+    match scrubbed.char_indices().nth(MAX_ERROR_BODY_CHARS) {
+        Some((end, _)) => format!("{}…", &scrubbed[..end]),
+        None => scrubbed,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn drops_sensitive_json_fields_and_tokens_in_text() {
+        let body = r#"{"error":{"message":"bad token sk-ant-REDACTED","access_token":"leaked"},"detail":"key 9f8e7d6c5b4a39281706f5e4d3c2b1a0ffee"}"#;
+
+        let scrubbed = scrub(body);
+
+        assert!(!scrubbed.contains("sk-ant-oat01"));
+        assert!(!scrubbed.contains("leaked"));
+        assert!(!scrubbed.contains("9f8e7d6c5b4a"));
+        assert!(scrubbed.contains("bad token [REDACTED]"));
+        assert!(scrubbed.contains(r#""access_token":"[REDACTED]""#));
+    }
+
+    #[test]
+    fn keeps_ordinary_words() {
+        let text = "invalid_grant: refresh token expired at 2030-01-01T00:00:00Z";
+
+        assert_eq!(scrub(text), text);
+    }
+
+    #[test]
+    fn shortens_long_error_bodies() {
+        let body = "x ".repeat(MAX_ERROR_BODY_CHARS);
+
+        let scrubbed = scrub_error_body(&body);
+
+        assert_eq!(scrubbed.chars().count(), MAX_ERROR_BODY_CHARS + 1);
+        assert!(scrubbed.ends_with('…'));
+    }
+}
@@ -0,0 +1,209 @@
+//! Linux Secret Service access over the D-Bus session bus, the counterpart
+//! of the `security` CLI used for the macOS Keychain. Items are found by
+//! their `service` attribute, as `secret-tool lookup service <name>` does.
+
+use std::collections::HashMap;
+use std::sync::mpsc;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use zbus::blocking::{Connection, Proxy};
+use zbus::zvariant::{ObjectPath, OwnedObjectPath, OwnedValue, Type, Value};
+use zeroize::{Zeroize, Zeroizing};
+
+use crate::models::{ClaudeOAuth, CredentialsFile};
+use crate::services::credential_store::{merge_oauth_json, CredentialError};
+
+const BUS_NAME: &str = "org.freedesktop.secrets";
+const SERVICE_PATH: &str = "/org/freedesktop/secrets";
+const SERVICE_INTERFACE: &str = "org.freedesktop.Secret.Service";
+const ITEM_INTERFACE: &str = "org.freedesktop.Secret.Item";
+const SESSION_INTERFACE: &str = "org.freedesktop.Secret.Session";
+const PROMPT_INTERFACE: &str = "org.freedesktop.Secret.Prompt";
+
+/// For calls the keyring answers by itself
+const CALL_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Leaves time to answer a keyring unlock prompt
+const PROMPT_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// A secret as the Secret Service API passes it, `(oayays)`
+#[derive(Serialize, Deserialize, Type)]
+struct Secret {
+    session: OwnedObjectPath,
+    parameters: Vec<u8>,
+    value: Vec<u8>,
+    content_type: String,
+}
+
+impl Drop for Secret {
+    fn drop(&mut self) {
+        self.value.zeroize();
+    }
+}
+
+fn call_error(e: zbus::Error) -> CredentialError {
+    match &e {
+        zbus::Error::MethodError(name, _, _)
+            if name.as_str() == "org.freedesktop.DBus.Error.ServiceUnknown" =>
+        {
+            CredentialError::Unavailable(
+                "no Secret Service is running; start a keyring such as GNOME Keyring or KWallet"
+                    .to_string(),
+            )
+        }
+        _ => CredentialError::SecretServiceError(e.to_string()),
+    }
+}
+
+/// A connection to the keyring with a session open. The session uses the
+/// `plain` algorithm: secrets cross the session bus, which only the user's
+/// own processes can reach, unencrypted.
+struct Session {
+    conn: Connection,
+    path: OwnedObjectPath,
+}
+
+impl Session {
+    fn open() -> Result<Self, CredentialError> {
+        let conn = zbus::blocking::connection::Builder::session()
+            .map(|builder| builder.method_timeout(CALL_TIMEOUT))
+            .and_then(|builder| builder.build())
+            .map_err(|e| CredentialError::Unavailable(format!("no D-Bus session bus: {}", e)))?;
+        let service =
+            Proxy::new(&conn, BUS_NAME, SERVICE_PATH, SERVICE_INTERFACE).map_err(call_error)?;
+        let (_, path): (OwnedValue, OwnedObjectPath) = service
+            .call("OpenSession", &("plain", Value::from("")))
+            .map_err(call_error)?;
+        Ok(Self { conn, path })
+    }
+
+    fn proxy(
+        &self,
+        path: impl Into<ObjectPath<'static>>,
+        interface: &'static str,
+    ) -> Result<Proxy<'static>, CredentialError> {
+        Proxy::new(&self.conn, BUS_NAME, path.into(), interface).map_err(call_error)
+    }
+
+    fn service(&self) -> Result<Proxy<'static>, CredentialError> {
+        self.proxy(
+            ObjectPath::from_static_str_unchecked(SERVICE_PATH),
+            SERVICE_INTERFACE,
+        )
+    }
+
+    /// Wait for the user to answer `prompt`; `/` means none was needed
+    fn complete(&self, prompt: OwnedObjectPath) -> Result<(), CredentialError> {
+        if prompt.as_str() == "/" {
+            return Ok(());
+        }
+        let proxy = self.proxy(prompt, PROMPT_INTERFACE)?;
+        // Subscribed before prompting, so the answer can't be missed
+        let mut completed = proxy.receive_signal("Completed").map_err(call_error)?;
+        proxy
+            .call::<_, _, ()>("Prompt", &("",))
+            .map_err(call_error)?;
+
+        // The iterator can't time out, so it waits on a thread of its own
+        let (sender, receiver) = mpsc::channel();
+        std::thread::spawn(move || {
+            let _ = sender.send(completed.next());
+        });
+        let message = receiver
+            .recv_timeout(PROMPT_TIMEOUT)
+            .ok()
+            .flatten()
+            .ok_or_else(|| {
+                CredentialError::SecretServiceError("the keyring prompt got no answer".to_string())
+            })?;
+        let (dismissed, _): (bool, OwnedValue) =
+            message.body().deserialize().map_err(call_error)?;
+        if dismissed {
+            return Err(CredentialError::SecretServiceError(
+                "unlocking the keyring was dismissed".to_string(),
+            ));
+        }
+        Ok(())
+    }
+
+    /// Unlock items or collections, prompting the user if the keyring asks to
+    fn unlock(&self, objects: Vec<OwnedObjectPath>) -> Result<(), CredentialError> {
+        let (_, prompt): (Vec<OwnedObjectPath>, OwnedObjectPath) = self
+            .service()?
+            .call("Unlock", &(objects,))
+            .map_err(call_error)?;
+        self.complete(prompt)
+    }
+
+    /// The first item stored under `service`, unlocked, if there is one
+    fn find(&self, service: &str) -> Result<Option<OwnedObjectPath>, CredentialError> {
+        let attributes = HashMap::from([("service", service)]);
+        let (unlocked, locked): (Vec<OwnedObjectPath>, Vec<OwnedObjectPath>) = self
+            .service()?
+            .call("SearchItems", &(attributes,))
+            .map_err(call_error)?;
+        if let Some(item) = unlocked.into_iter().next() {
+            return Ok(Some(item));
+        }
+        let Some(item) = locked.into_iter().next() else {
+            return Ok(None);
+        };
+        self.unlock(vec![item.clone()])?;
+        Ok(Some(item))
+    }
+
+    fn secret(&self, item: &OwnedObjectPath) -> Result<Zeroizing<String>, CredentialError> {
+        // One struct argument, not four
+        let (secret,): (Secret,) = self
+            .proxy(item.clone(), ITEM_INTERFACE)?
+            .call("GetSecret", &(&self.path,))
+            .map_err(call_error)?;
+        Ok(Zeroizing::new(
+            String::from_utf8_lossy(&secret.value).into_owned(),
+        ))
+    }
+
+    fn new_secret(&self, content: &str) -> Secret {
+        Secret {
+            session: self.path.clone(),
+            parameters: Vec::new(),
+            value: content.as_bytes().to_vec(),
+            content_type: "text/plain".to_string(),
+        }
+    }
+}
+
+impl Drop for Session {
+    fn drop(&mut self) {
+        if let Ok(session) = self.proxy(self.path.clone(), SESSION_INTERFACE) {
+            let _ = session.call::<_, _, ()>("Close", &());
+        }
+    }
+}
+
+pub(crate) fn read(service: &str) -> Result<ClaudeOAuth, CredentialError> {
+    let session = Session::open()?;
+    let item = session.find(service)?.ok_or(CredentialError::NotFound)?;
+    let content = session.secret(&item)?;
+    let creds: CredentialsFile = serde_json::from_str(content.trim())?;
+    creds
+        .claude_ai_oauth
+        .ok_or(CredentialError::NoOAuthCredentials)
+}
+
+/// Write refreshed tokens back to the item they were read from, keeping any
+/// other fields in the stored document and the item's label and attributes
+pub(crate) fn store(service: &str, oauth: &ClaudeOAuth) -> Result<(), CredentialError> {
+    let session = Session::open()?;
+    let item = session.find(service)?.ok_or_else(|| {
+        CredentialError::WriteError(format!("no Secret Service item for {}", service))
+    })?;
+
+    let existing = session.secret(&item)?;
+    let content = merge_oauth_json(existing.trim(), oauth)?;
+    session
+        .proxy(item, ITEM_INTERFACE)?
+        .call::<_, _, ()>("SetSecret", &(session.new_secret(&content),))
+        .map_err(|e| CredentialError::WriteError(e.to_string()))
+}
@@ -0,0 +1,74 @@
+//! Upgrades for `settings.json` files written by older versions of the app
+
+use serde_json::{Map, Value};
+
+/// Version written by this build
+pub const CURRENT_SCHEMA_VERSION: u32 = 1;
+
+type Migration = fn(&mut Map<String, Value>);
+
+/// `MIGRATIONS[n]` upgrades a version `n` file to `n + 1`. Files without a
+/// `schema_version` predate it and count as version 0. Append only: a
+/// released migration never changes, and each one converts a format that
+/// a release actually wrote.
+const MIGRATIONS: &[Migration] = &[add_schema_version];
+
+/// Stored version of a parsed settings file
+pub fn schema_version(settings: &Value) -> u32 {
+    settings
+        .get("schema_version")
+        .and_then(Value::as_u64)
+        .map_or(0, |version| version.min(u32::MAX as u64) as u32)
+}
+
+/// Run every migration newer than the file's version, in order. Files from
+/// a newer build are left alone, keeping their version. Returns the version
+/// the file had.
+pub fn migrate(settings: &mut Value) -> u32 {
+    let from = schema_version(settings);
+    let Some(fields) = settings.as_object_mut() else {
+        // Not settings at all; deserializing reports it
+        return from;
+    };
+    for (version, migration) in MIGRATIONS.iter().enumerate().skip(from as usize) {
+        migration(fields);
+        fields.insert(
+            "schema_version".to_string(),
+            Value::from(version as u32 + 1),
+        );
+    }
+    from
+}
+
+/// v0 → v1: releases before `schema_version` wrote the same fields, and
+/// everything added since has a default, so only the version is new
+fn add_schema_version(_settings: &mut Map<String, Value>) {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn upgrades_unversioned_files_to_the_current_version() {
+        let mut settings = json!({
+            "shortcut": { "modifier": "ctrl+shift", "key": "u" },
+            "first_launch": false,
+            "layout": { "layout_type": "detailed" }
+        });
+        let mut expected = settings.clone();
+        expected["schema_version"] = json!(CURRENT_SCHEMA_VERSION);
+
+        assert_eq!(migrate(&mut settings), 0);
+        assert_eq!(settings, expected);
+    }
+
+    #[test]
+    fn leaves_files_from_newer_builds_alone() {
+        let original = json!({ "schema_version": 99, "layout": "compact" });
+        let mut settings = original.clone();
+
+        assert_eq!(migrate(&mut settings), 99);
+        assert_eq!(settings, original);
+    }
+}
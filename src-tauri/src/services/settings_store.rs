@@ -1,38 +1,214 @@
 use serde::{Deserialize, Serialize};
-use std::path::PathBuf;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, PoisonError};
+use thiserror::Error;
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+use crate::services::settings_migrations::{self, CURRENT_SCHEMA_VERSION};
+use crate::services::{app_dirs, diagnostics};
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
 #[serde(rename_all = "snake_case")]
 pub enum LayoutType {
+    #[default]
     Simple,
     Detailed,
 }
 
-impl Default for LayoutType {
-    fn default() -> Self {
-        Self::Simple
-    }
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct LayoutConfig {
+    pub layout_type: LayoutType,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct LayoutConfig {
-    pub layout_type: LayoutType,
+pub struct AppSettings {
+    /// Format of the file, see `settings_migrations`
+    #[serde(default = "current_schema_version")]
+    pub schema_version: u32,
+    pub shortcut: ShortcutConfig,
+    pub first_launch: bool,
+    #[serde(default)]
+    pub layout: LayoutConfig,
+    /// Overrides the Anthropic API base URL (e.g. a local mock server)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub api_base_url: Option<String>,
+    #[serde(default)]
+    pub network: NetworkConfig,
+    /// Overrides for the OAuth endpoints used by the in-app login
+    #[serde(default)]
+    pub oauth: OAuthEndpoints,
+    /// Overrides the default credentials file location
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub credentials_path: Option<String>,
+    /// Ordered chain of places to look for credentials
+    #[serde(default = "default_credential_providers")]
+    pub credential_providers: Vec<CredentialSource>,
+    /// Named accounts; empty means one implicit profile using `credential_providers`
+    #[serde(default)]
+    pub profiles: Vec<Profile>,
+    /// Profile the dashboard and overlay show by default
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub active_profile: Option<String>,
+    #[serde(default)]
+    pub expiry_warnings: ExpiryWarningConfig,
+    /// External terminal for the `claude` login, with `{title}` and `{cmd}`
+    /// placeholders; `None` picks one automatically
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub terminal_command: Option<String>,
+    /// Keys this build doesn't know, such as ones from a newer build or set
+    /// aside under `unrecognized`, written back unchanged
+    #[serde(flatten)]
+    pub other: serde_json::Map<String, serde_json::Value>,
+}
+
+/// When to warn that an access token is about to expire
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ExpiryWarningConfig {
+    /// Minutes before expiry at which to warn, each once per token
+    #[serde(default = "default_expiry_thresholds")]
+    pub minutes_before: Vec<u32>,
+    /// Show desktop notifications in addition to the `auth-expiring` event
+    #[serde(default = "default_true")]
+    pub desktop_notifications: bool,
 }
 
-impl Default for LayoutConfig {
+fn current_schema_version() -> u32 {
+    CURRENT_SCHEMA_VERSION
+}
+
+fn default_expiry_thresholds() -> Vec<u32> {
+    vec![30, 5]
+}
+
+fn default_true() -> bool {
+    true
+}
+
+impl Default for ExpiryWarningConfig {
     fn default() -> Self {
         Self {
-            layout_type: LayoutType::default(),
+            minutes_before: default_expiry_thresholds(),
+            desktop_notifications: true,
         }
     }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct AppSettings {
-    pub shortcut: ShortcutConfig,
-    pub first_launch: bool,
+/// Id of the implicit profile used when none are configured
+pub const DEFAULT_PROFILE_ID: &str = "default";
+
+/// A named account monitored separately, e.g. personal and work subscriptions
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Profile {
+    pub id: String,
+    pub name: String,
+    #[serde(default = "default_credential_providers")]
+    pub credential_providers: Vec<CredentialSource>,
+}
+
+impl AppSettings {
+    /// Configured profiles, or the implicit default profile when there are none
+    pub fn effective_profiles(&self) -> Vec<Profile> {
+        if !self.profiles.is_empty() {
+            return self.profiles.clone();
+        }
+        vec![Profile {
+            id: DEFAULT_PROFILE_ID.to_string(),
+            name: "Default".to_string(),
+            credential_providers: self.credential_providers.clone(),
+        }]
+    }
+
+    /// Profile `id`, or the active profile when `id` is `None`
+    pub fn profile(&self, id: Option<&str>) -> Option<Profile> {
+        let profiles = self.effective_profiles();
+        if let Some(id) = id {
+            return profiles.into_iter().find(|profile| profile.id == id);
+        }
+        // A stale `active_profile` falls back to the first profile
+        let active = self.active_profile.as_deref();
+        profiles
+            .iter()
+            .find(|profile| Some(profile.id.as_str()) == active)
+            .or(profiles.first())
+            .cloned()
+    }
+}
+
+/// Profile ids end up in file names, so keep them to `[A-Za-z0-9_-]`
+pub fn is_valid_profile_id(id: &str) -> bool {
+    !id.is_empty()
+        && id
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+}
+
+/// Environment variable Claude Code reads long-lived OAuth tokens from
+pub const DEFAULT_TOKEN_ENV: &str = "CLAUDE_CODE_OAUTH_TOKEN";
+
+fn default_token_env() -> String {
+    DEFAULT_TOKEN_ENV.to_string()
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum CredentialSource {
+    /// Access token in an environment variable
+    Env {
+        #[serde(default = "default_token_env")]
+        var: String,
+    },
+    /// Credentials file at an explicit path
+    File { path: String },
+    /// `~/.claude/.credentials.json`
+    DefaultFile,
+    /// macOS Keychain entry written by Claude Code
+    Keychain,
+    /// Linux Secret Service (GNOME Keyring, KWallet) item with this `service` attribute
+    SecretService {
+        #[serde(default = "default_secret_service")]
+        service: String,
+    },
+    /// Command that prints credentials JSON, like AWS `credential_process`
+    Process { command: String },
+}
+
+/// Same name Claude Code uses for its macOS Keychain entry
+pub const DEFAULT_SECRET_SERVICE: &str = "Claude Code-credentials";
+
+fn default_secret_service() -> String {
+    DEFAULT_SECRET_SERVICE.to_string()
+}
+
+pub fn default_credential_providers() -> Vec<CredentialSource> {
+    vec![
+        CredentialSource::Env {
+            var: default_token_env(),
+        },
+        CredentialSource::DefaultFile,
+        CredentialSource::Keychain,
+    ]
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct OAuthEndpoints {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub authorize_url: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub token_url: Option<String>,
+}
+
+/// Proxy and TLS trust settings for corporate networks
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct NetworkConfig {
+    /// `http://`, `https://` or `socks5://` proxy, optionally with `user:pass@`
     #[serde(default)]
-    pub layout: LayoutConfig,
+    pub proxy_url: Option<String>,
+    /// Comma-separated hosts that bypass the proxy, as in `NO_PROXY`
+    #[serde(default)]
+    pub no_proxy: Option<String>,
+    /// PEM files with extra CA certificates to trust
+    #[serde(default)]
+    pub ca_cert_paths: Vec<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -44,9 +220,20 @@ pub struct ShortcutConfig {
 impl Default for AppSettings {
     fn default() -> Self {
         Self {
+            schema_version: CURRENT_SCHEMA_VERSION,
             shortcut: ShortcutConfig::default(),
             first_launch: true,
             layout: LayoutConfig::default(),
+            api_base_url: None,
+            network: NetworkConfig::default(),
+            oauth: OAuthEndpoints::default(),
+            credentials_path: None,
+            credential_providers: default_credential_providers(),
+            profiles: Vec::new(),
+            active_profile: None,
+            expiry_warnings: ExpiryWarningConfig::default(),
+            terminal_command: None,
+            other: serde_json::Map::new(),
         }
     }
 }
@@ -87,36 +274,248 @@ impl Default for ShortcutConfig {
     }
 }
 
-pub fn get_settings_path() -> PathBuf {
-    let home = dirs::home_dir().expect("Could not find home directory");
-    home.join(".claude-usage-monitor").join("settings.json")
+pub fn get_settings_path() -> std::io::Result<PathBuf> {
+    Ok(app_dirs::config_dir()?.join("settings.json"))
 }
 
-pub fn load_settings() -> AppSettings {
-    let path = get_settings_path();
-    if path.exists() {
-        std::fs::read_to_string(&path)
-            .ok()
-            .and_then(|s| serde_json::from_str(&s).ok())
-            .unwrap_or_default()
-    } else {
-        AppSettings::default()
+#[derive(Error, Debug)]
+pub enum SettingsError {
+    #[error("Failed to access settings: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("Settings file {} is corrupt: {message}", path.display())]
+    Corrupt { path: PathBuf, message: String },
+
+    #[error("Failed to serialize settings: {0}")]
+    Serialize(#[from] serde_json::Error),
+}
+
+/// What startup recovery did with a corrupt settings file
+#[derive(Debug, Clone, Serialize)]
+pub struct SettingsRecovery {
+    /// Where the corrupt file was moved, kept for the user to inspect
+    pub quarantined_path: String,
+    pub restored_from_backup: bool,
+    pub error: String,
+}
+
+/// Set by `recover_corrupt_settings` until the frontend has shown it
+static RECOVERY: Mutex<Option<SettingsRecovery>> = Mutex::new(None);
+
+/// How many earlier versions of `settings.json` are kept
+const BACKUP_GENERATIONS: u32 = 3;
+
+/// `settings.json.bak.<generation>`, 1 being the newest
+fn backup_path(path: &Path, generation: u32) -> PathBuf {
+    path.with_extension(format!("json.bak.{}", generation))
+}
+
+/// Shift each backup one generation older, dropping the oldest, and keep
+/// the current file as the newest
+fn rotate_backups(path: &Path) -> std::io::Result<()> {
+    match std::fs::remove_file(backup_path(path, BACKUP_GENERATIONS)) {
+        Err(e) if e.kind() != std::io::ErrorKind::NotFound => return Err(e),
+        _ => {}
+    }
+    for generation in (1..BACKUP_GENERATIONS).rev() {
+        let older = backup_path(path, generation);
+        if older.exists() {
+            std::fs::rename(&older, backup_path(path, generation + 1))?;
+        }
+    }
+    std::fs::copy(path, backup_path(path, 1))?;
+    Ok(())
+}
+
+/// Where values this build can't read are kept
+const UNRECOGNIZED_KEY: &str = "unrecognized";
+
+/// Move top-level values this build can't read, such as a layout added by a
+/// newer build, under `unrecognized` so the rest of the file still loads.
+/// Returns the keys moved.
+fn set_aside_unrecognized(value: &mut serde_json::Value) -> Vec<String> {
+    let Ok(serde_json::Value::Object(defaults)) = serde_json::to_value(AppSettings::default())
+    else {
+        return Vec::new();
+    };
+    let Some(fields) = value.as_object_mut() else {
+        return Vec::new();
+    };
+    let unreadable: Vec<String> = fields
+        .iter()
+        .filter(|(key, field)| {
+            let mut probe = defaults.clone();
+            probe.insert(key.to_string(), (*field).clone());
+            serde_json::from_value::<AppSettings>(serde_json::Value::Object(probe)).is_err()
+        })
+        .map(|(key, _)| key.clone())
+        .collect();
+    if unreadable.is_empty() {
+        return unreadable;
+    }
+
+    let mut aside = match fields.remove(UNRECOGNIZED_KEY) {
+        Some(serde_json::Value::Object(aside)) => aside,
+        Some(earlier) => serde_json::Map::from_iter([(UNRECOGNIZED_KEY.to_string(), earlier)]),
+        None => serde_json::Map::new(),
+    };
+    for key in &unreadable {
+        if let Some(field) = fields.remove(key) {
+            aside.insert(key.clone(), field);
+        }
+    }
+    fields.insert(
+        UNRECOGNIZED_KEY.to_string(),
+        serde_json::Value::Object(aside),
+    );
+    unreadable
+}
+
+/// Read and migrate a settings file to the current schema, in memory only.
+/// Also returns the keys set aside to read it.
+fn parse_settings(path: &Path) -> Result<(AppSettings, Vec<String>), SettingsError> {
+    let corrupt = |e: serde_json::Error| SettingsError::Corrupt {
+        path: path.to_path_buf(),
+        message: e.to_string(),
+    };
+    let content = std::fs::read_to_string(path)?;
+    let mut value: serde_json::Value = serde_json::from_str(&content).map_err(corrupt)?;
+    settings_migrations::migrate(&mut value);
+    match serde_json::from_value(value.clone()) {
+        Ok(settings) => Ok((settings, Vec::new())),
+        Err(e) => {
+            let set_aside = set_aside_unrecognized(&mut value);
+            if set_aside.is_empty() {
+                return Err(corrupt(e));
+            }
+            let settings = serde_json::from_value(value).map_err(corrupt)?;
+            Ok((settings, set_aside))
+        }
     }
 }
 
-pub fn save_settings(settings: &AppSettings) -> Result<(), std::io::Error> {
-    let path = get_settings_path();
+/// Stored settings, or defaults when there is no settings file yet
+pub fn load_settings() -> Result<AppSettings, SettingsError> {
+    let path = get_settings_path()?;
+    if !path.exists() {
+        return Ok(AppSettings::default());
+    }
+    parse_settings(&path).map(|(settings, _)| settings)
+}
+
+/// Settings for code that only reads them. An unreadable file yields the
+/// defaults here, but writers go through `load_settings` and refuse, so the
+/// file is never overwritten with them.
+pub fn load_settings_or_default() -> AppSettings {
+    load_settings().unwrap_or_else(|e| {
+        diagnostics::record_error("settings", &e.to_string());
+        AppSettings::default()
+    })
+}
+
+/// Write to a temporary file and rename it over `settings.json`, so a crash
+/// leaves either the old or the new file. The previous file, if it parsed,
+/// becomes `settings.json.bak.1`, shifting older backups up to
+/// `settings.json.bak.3`.
+pub fn save_settings(settings: &AppSettings) -> Result<(), SettingsError> {
+    let path = get_settings_path()?;
     if let Some(parent) = path.parent() {
         std::fs::create_dir_all(parent)?;
     }
     let json = serde_json::to_string_pretty(settings)?;
-    std::fs::write(path, json)
+
+    let tmp = path.with_extension("json.tmp");
+    {
+        let mut file = std::fs::File::create(&tmp)?;
+        file.write_all(json.as_bytes())?;
+        file.sync_all()?;
+    }
+
+    // A corrupt file never replaces a good backup
+    if parse_settings(&path).is_ok() {
+        rotate_backups(&path)?;
+    }
+    std::fs::rename(&tmp, &path)?;
+    Ok(())
+}
+
+/// Move a corrupt `settings.json` aside and restore the newest backup that
+/// is usable. Runs once at startup, before anything reads settings.
+pub fn recover_corrupt_settings() -> Option<SettingsRecovery> {
+    let path = get_settings_path().ok()?;
+    let Err(error @ SettingsError::Corrupt { .. }) = load_settings() else {
+        return None;
+    };
+
+    let stamp = chrono::Local::now().format("%Y%m%d-%H%M%S");
+    let quarantined = path.with_extension(format!("json.corrupt-{}", stamp));
+    if let Err(e) = std::fs::rename(&path, &quarantined) {
+        diagnostics::record_error("settings", &format!("quarantine failed: {}", e));
+        return None;
+    }
+
+    let restored_from_backup = (1..=BACKUP_GENERATIONS)
+        .map(|generation| backup_path(&path, generation))
+        .any(|backup| parse_settings(&backup).is_ok() && std::fs::copy(&backup, &path).is_ok());
+
+    let recovery = SettingsRecovery {
+        quarantined_path: quarantined.display().to_string(),
+        restored_from_backup,
+        error: error.to_string(),
+    };
+    diagnostics::record_error("settings", &recovery.error);
+    *RECOVERY.lock().unwrap_or_else(PoisonError::into_inner) = Some(recovery.clone());
+    Some(recovery)
+}
+
+/// Rewrite an older `settings.json` in the current schema, or one with
+/// values set aside under `unrecognized`, keeping the original as
+/// `settings.json.v<N>.bak` for a downgrade. Runs once at startup, after
+/// `recover_corrupt_settings`.
+pub fn upgrade_settings_file() -> Result<(), SettingsError> {
+    let path = get_settings_path()?;
+    if !path.exists() {
+        return Ok(());
+    }
+    let content = std::fs::read_to_string(&path)?;
+    let Ok(stored) = serde_json::from_str::<serde_json::Value>(&content) else {
+        // Left to recovery
+        return Ok(());
+    };
+    let version = settings_migrations::schema_version(&stored);
+    let (settings, set_aside) = parse_settings(&path)?;
+    if !set_aside.is_empty() {
+        diagnostics::record_error(
+            "settings",
+            &format!(
+                "moved settings this version can't read under \"{}\": {}",
+                UNRECOGNIZED_KEY,
+                set_aside.join(", ")
+            ),
+        );
+    }
+    if version >= CURRENT_SCHEMA_VERSION && set_aside.is_empty() {
+        return Ok(());
+    }
+
+    std::fs::copy(&path, path.with_extension(format!("json.v{}.bak", version)))?;
+    save_settings(&settings)
+}
+
+/// The recovery from this run, returned once
+pub fn take_settings_recovery() -> Option<SettingsRecovery> {
+    RECOVERY
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+        .take()
 }
 
 /// 설정 파일을 삭제하여 최초 실행 상태로 리셋
 #[allow(dead_code)]
 pub fn reset_settings() {
-    let path = get_settings_path();
+    let Ok(path) = get_settings_path() else {
+        return;
+    };
     if path.exists() {
         let _ = std::fs::remove_file(&path);
     }
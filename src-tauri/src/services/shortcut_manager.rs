@@ -0,0 +1,107 @@
+//! The global shortcut that toggles the overlay, re-registered while the app
+//! runs whenever the setting changes
+
+use std::sync::{Mutex, PoisonError};
+
+use tauri::{AppHandle, Emitter, Manager};
+use tauri_plugin_global_shortcut::{GlobalShortcutExt, Shortcut, ShortcutState};
+use thiserror::Error;
+
+use crate::platform;
+use crate::services::accelerator::AcceleratorError;
+use crate::services::settings_store::ShortcutConfig;
+
+#[derive(Error, Debug)]
+pub enum ShortcutError {
+    #[error("Invalid shortcut: {0}")]
+    Invalid(#[from] AcceleratorError),
+
+    #[error("Couldn't register {shortcut}, it may be taken by another app ({message}). {kept}")]
+    Register {
+        shortcut: String,
+        message: String,
+        /// What is bound now that the change was rolled back
+        kept: String,
+    },
+
+    #[error("Couldn't release the current shortcut {shortcut}: {message}")]
+    Unregister { shortcut: String, message: String },
+}
+
+/// The registered overlay shortcut, managed as app state
+#[derive(Default)]
+pub struct ShortcutManager {
+    current: Mutex<Option<Shortcut>>,
+}
+
+fn toggle_overlay(app: &AppHandle) {
+    if let Some(window) = app.get_webview_window("main") {
+        if window.is_visible().unwrap_or(false) {
+            let _ = window.hide();
+            let _ = window.emit("overlay-visibility", false);
+        } else {
+            let _ = window.show();
+            let _ = window.emit("overlay-visibility", true);
+        }
+    }
+}
+
+fn register(app: &AppHandle, shortcut: Shortcut) -> Result<(), String> {
+    app.global_shortcut()
+        .on_shortcut(shortcut, |app, _shortcut, event| {
+            if event.state() == ShortcutState::Pressed {
+                toggle_overlay(app);
+            }
+        })
+        .map_err(|e| e.to_string())
+}
+
+impl ShortcutManager {
+    /// Bind `config`, replacing the current shortcut. Either the new shortcut
+    /// ends up registered or the previous one is restored; an invalid
+    /// `config` changes nothing.
+    pub fn apply(&self, app: &AppHandle, config: &ShortcutConfig) -> Result<(), ShortcutError> {
+        let shortcut = platform::parse_shortcut(config)?;
+
+        // Held throughout, so concurrent changes can't interleave
+        let mut current = self.current.lock().unwrap_or_else(PoisonError::into_inner);
+        let previous = *current;
+        if previous == Some(shortcut) {
+            return Ok(());
+        }
+
+        if let Some(previous) = previous {
+            app.global_shortcut()
+                .unregister(previous)
+                .map_err(|e| ShortcutError::Unregister {
+                    shortcut: previous.to_string(),
+                    message: e.to_string(),
+                })?;
+            *current = None;
+        }
+
+        match register(app, shortcut) {
+            Ok(()) => {
+                *current = Some(shortcut);
+                Ok(())
+            }
+            Err(message) => {
+                let kept = match previous {
+                    Some(previous) => match register(app, previous) {
+                        Ok(()) => {
+                            *current = Some(previous);
+                            format!("Kept {}.", previous)
+                        }
+                        Err(e) => format!("Restoring {} also failed: {}", previous, e),
+                    },
+                    None => "No shortcut is registered.".to_string(),
+                };
+                Err(ShortcutError::Register {
+                    shortcut: shortcut.to_string(),
+                    message,
+                    kept,
+                })
+            }
+        }
+    }
+}
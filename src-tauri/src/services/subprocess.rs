@@ -0,0 +1,104 @@
+//! Helper commands (`security`, a credential process, `claude --version`)
+//! run with a deadline, so one that hangs can't hold up its caller forever
+
+use std::io::{Read, Write};
+use std::process::{Command, Output, Stdio};
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+
+const POLL_INTERVAL: Duration = Duration::from_millis(20);
+
+/// Read a pipe to the end on its own thread, so a command writing more than
+/// the pipe holds doesn't stall waiting for us
+fn drain(mut pipe: impl Read + Send + 'static) -> JoinHandle<Vec<u8>> {
+    std::thread::spawn(move || {
+        let mut buf = Vec::new();
+        let _ = pipe.read_to_end(&mut buf);
+        buf
+    })
+}
+
+fn collect(pipe: Option<JoinHandle<Vec<u8>>>) -> Vec<u8> {
+    pipe.and_then(|handle| handle.join().ok())
+        .unwrap_or_default()
+}
+
+/// Like `Command::output`, writing `input` to stdin first. The command is
+/// killed once `timeout` passes and a `TimedOut` error returned.
+pub(crate) fn output_with_timeout(
+    command: &mut Command,
+    input: Option<&[u8]>,
+    timeout: Duration,
+) -> std::io::Result<Output> {
+    let mut child = command
+        .stdin(if input.is_some() {
+            Stdio::piped()
+        } else {
+            Stdio::null()
+        })
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()?;
+
+    let stdout = child.stdout.take().map(drain);
+    let stderr = child.stderr.take().map(drain);
+    if let (Some(input), Some(mut stdin)) = (input, child.stdin.take()) {
+        // A command that exits without reading its input is reported by its status
+        let _ = stdin.write_all(input);
+    }
+
+    let started = Instant::now();
+    let status = loop {
+        if let Some(status) = child.try_wait()? {
+            break status;
+        }
+        if started.elapsed() >= timeout {
+            let _ = child.kill();
+            let _ = child.wait();
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::TimedOut,
+                format!("no answer after {}s", timeout.as_secs()),
+            ));
+        }
+        std::thread::sleep(POLL_INTERVAL);
+    };
+
+    Ok(Output {
+        status,
+        stdout: collect(stdout),
+        stderr: collect(stderr),
+    })
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn returns_output_and_passes_input() {
+        let output = output_with_timeout(
+            Command::new("sh").args(["-c", "cat; echo oops >&2"]),
+            Some(b"secret"),
+            Duration::from_secs(5),
+        )
+        .unwrap();
+
+        assert!(output.status.success());
+        assert_eq!(output.stdout, b"secret");
+        assert_eq!(output.stderr, b"oops\n");
+    }
+
+    #[test]
+    fn kills_a_command_that_hangs() {
+        let started = Instant::now();
+        let error = output_with_timeout(
+            Command::new("sh").args(["-c", "exec sleep 30"]),
+            None,
+            Duration::from_millis(200),
+        )
+        .unwrap_err();
+
+        assert_eq!(error.kind(), std::io::ErrorKind::TimedOut);
+        assert!(started.elapsed() < Duration::from_secs(5));
+    }
+}
@@ -0,0 +1,321 @@
+//! Opening `claude` in an external terminal emulator and closing it again
+
+use std::process::{Child, Command, Stdio};
+use std::sync::{Mutex, PoisonError};
+
+use thiserror::Error;
+
+use crate::services::claude_cli;
+
+/// Window title for terminals that accept one
+pub const WINDOW_TITLE: &str = "Claude Login";
+
+/// Arguments for terminals we know, with `{title}` and `{cmd}` placeholders.
+/// Flags keep the spawned process alive for as long as its window, so that
+/// closing it by PID works for terminals that otherwise hand off to a server.
+#[cfg(target_os = "linux")]
+const KNOWN_TERMINALS: &[(&str, &[&str])] = &[
+    ("kitty", &["--title", "{title}", "-e", "{cmd}"]),
+    ("alacritty", &["--title", "{title}", "-e", "{cmd}"]),
+    ("wezterm", &["start", "--always-new-process", "--", "{cmd}"]),
+    ("foot", &["--title", "{title}", "{cmd}"]),
+    (
+        "gnome-terminal",
+        &["--wait", "--title={title}", "--", "{cmd}"],
+    ),
+    (
+        "konsole",
+        &["--nofork", "-p", "tabtitle={title}", "-e", "{cmd}"],
+    ),
+    (
+        "xfce4-terminal",
+        &["--disable-server", "--title={title}", "-x", "{cmd}"],
+    ),
+    (
+        "tilix",
+        &["--new-process", "--title={title}", "-e", "{cmd}"],
+    ),
+    (
+        "terminator",
+        &["--no-dbus", "--title={title}", "-x", "{cmd}"],
+    ),
+    ("xterm", &["-title", "{title}", "-e", "{cmd}"]),
+];
+
+/// Debian's `x-terminal-emulator` contract, also a fair guess for `$TERMINAL`
+#[cfg(target_os = "linux")]
+const GENERIC_ARGS: &[&str] = &["-T", "{title}", "-e", "{cmd}"];
+
+/// The terminal started by the last `launch`, if we can track it
+static LAUNCHED: Mutex<Option<Child>> = Mutex::new(None);
+
+#[derive(Error, Debug)]
+pub enum TerminalError {
+    #[error(
+        "No supported terminal emulator found; set $TERMINAL or a terminal command in settings"
+    )]
+    NotFound,
+
+    #[error("Invalid terminal command template: {0}")]
+    InvalidTemplate(String),
+
+    #[error("Failed to start {program}: {source}")]
+    Spawn {
+        program: String,
+        source: std::io::Error,
+    },
+}
+
+/// A terminal invocation with placeholders already filled in
+#[derive(Debug, Clone, PartialEq)]
+pub struct TerminalCommand {
+    pub program: String,
+    pub args: Vec<String>,
+}
+
+fn fill(arg: &str, title: &str, cmd: &str) -> String {
+    arg.replace("{title}", title).replace("{cmd}", cmd)
+}
+
+/// Parse a user template such as `kitty --title {title} -e {cmd}`. Words are
+/// split on whitespace before substitution, so a title with spaces stays one
+/// argument.
+pub fn parse_template(
+    template: &str,
+    title: &str,
+    cmd: &str,
+) -> Result<TerminalCommand, TerminalError> {
+    let mut words = template.split_whitespace();
+    let program = words
+        .next()
+        .ok_or_else(|| TerminalError::InvalidTemplate("the template is empty".to_string()))?;
+    if !template.contains("{cmd}") {
+        return Err(TerminalError::InvalidTemplate(
+            "the template must contain {cmd}".to_string(),
+        ));
+    }
+
+    Ok(TerminalCommand {
+        program: fill(program, title, cmd),
+        args: words.map(|word| fill(word, title, cmd)).collect(),
+    })
+}
+
+/// `program` itself when it is a path, otherwise the first match on `PATH`
+#[cfg(target_os = "linux")]
+fn find_program(program: &str) -> Option<std::path::PathBuf> {
+    let path = std::path::Path::new(program);
+    if path.components().count() > 1 {
+        return path.is_file().then(|| path.to_path_buf());
+    }
+    std::env::var_os("PATH")
+        .into_iter()
+        .flat_map(|paths| std::env::split_paths(&paths).collect::<Vec<_>>())
+        .map(|dir| dir.join(program))
+        .find(|candidate| candidate.is_file())
+}
+
+#[cfg(target_os = "linux")]
+fn known_args(name: &str) -> Option<&'static [&'static str]> {
+    KNOWN_TERMINALS
+        .iter()
+        .find(|(known, _)| *known == name)
+        .map(|(_, args)| *args)
+}
+
+/// Terminals to try in order: `$TERMINAL`, `x-terminal-emulator`, then the
+/// built-in table. Each one found on `PATH` uses the table's arguments when
+/// it, or what the alternatives symlink points at, is a terminal we know.
+#[cfg(target_os = "linux")]
+fn candidates(title: &str, cmd: &str) -> Vec<TerminalCommand> {
+    let preferred = std::env::var("TERMINAL")
+        .ok()
+        .filter(|terminal| !terminal.trim().is_empty());
+
+    preferred
+        .into_iter()
+        .chain(std::iter::once("x-terminal-emulator".to_string()))
+        .chain(KNOWN_TERMINALS.iter().map(|(name, _)| name.to_string()))
+        .filter_map(|program| {
+            let path = find_program(program.trim())?;
+            let resolved = std::fs::canonicalize(&path).unwrap_or_else(|_| path.clone());
+            let args = [&path, &resolved]
+                .iter()
+                .filter_map(|p| p.file_name()?.to_str())
+                .find_map(known_args)
+                .unwrap_or(GENERIC_ARGS);
+            Some(TerminalCommand {
+                program: path.display().to_string(),
+                args: args.iter().map(|arg| fill(arg, title, cmd)).collect(),
+            })
+        })
+        .collect()
+}
+
+/// Start the terminal in its own process group so the whole group can be
+/// closed later without touching the app
+fn spawn_tracked(command: &TerminalCommand) -> Result<(), TerminalError> {
+    let mut process = Command::new(&command.program);
+    process
+        .args(&command.args)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null());
+    #[cfg(unix)]
+    {
+        use std::os::unix::process::CommandExt;
+        process.process_group(0);
+    }
+
+    let child = process.spawn().map_err(|source| TerminalError::Spawn {
+        program: command.program.clone(),
+        source,
+    })?;
+    close_tracked();
+    *LAUNCHED.lock().unwrap_or_else(PoisonError::into_inner) = Some(child);
+    Ok(())
+}
+
+/// Open `claude` in a terminal: the settings template when given, otherwise
+/// the platform default
+pub fn launch(template: Option<&str>) -> Result<(), TerminalError> {
+    if let Some(template) = template.filter(|t| !t.trim().is_empty()) {
+        return spawn_tracked(&parse_template(
+            template,
+            WINDOW_TITLE,
+            &claude_cli::command(),
+        )?);
+    }
+    launch_default()
+}
+
+#[cfg(target_os = "linux")]
+fn launch_default() -> Result<(), TerminalError> {
+    let mut last_error = None;
+    for command in candidates(WINDOW_TITLE, &claude_cli::command()) {
+        match spawn_tracked(&command) {
+            Ok(()) => return Ok(()),
+            Err(e) => last_error = Some(e),
+        }
+    }
+    Err(last_error.unwrap_or(TerminalError::NotFound))
+}
+
+#[cfg(target_os = "macos")]
+fn launch_default() -> Result<(), TerminalError> {
+    // Terminal.app windows belong to Terminal itself, so they are found again by title
+    let script = format!(
+        r#"tell application "Terminal"
+            activate
+            set newTab to do script "{}"
+            set custom title of newTab to "{}"
+        end tell"#,
+        claude_cli::command(),
+        WINDOW_TITLE
+    );
+    Command::new("osascript")
+        .arg("-e")
+        .arg(script)
+        .spawn()
+        .map(|_| ())
+        .map_err(|source| TerminalError::Spawn {
+            program: "Terminal".to_string(),
+            source,
+        })
+}
+
+#[cfg(target_os = "windows")]
+fn launch_default() -> Result<(), TerminalError> {
+    // `start` detaches the console window, so it is found again by title
+    Command::new("cmd")
+        .args(["/c", "start", WINDOW_TITLE, "cmd", "/k"])
+        .arg(claude_cli::command())
+        .spawn()
+        .map(|_| ())
+        .map_err(|source| TerminalError::Spawn {
+            program: "Command Prompt".to_string(),
+            source,
+        })
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos", target_os = "windows")))]
+fn launch_default() -> Result<(), TerminalError> {
+    Err(TerminalError::NotFound)
+}
+
+/// Close the terminal from the last tracked launch, if it is still running
+fn close_tracked() -> bool {
+    let Some(mut child) = LAUNCHED
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+        .take()
+    else {
+        return false;
+    };
+    if matches!(child.try_wait(), Ok(Some(_))) {
+        return true;
+    }
+
+    // Its own process group, see `spawn_tracked`
+    #[cfg(unix)]
+    if let Some(pgid) = libc::pid_t::try_from(child.id())
+        .ok()
+        .filter(|pgid| *pgid > 0)
+    {
+        // SAFETY: killpg has no memory preconditions, and a positive pgid
+        // names only the terminal's group, never ours (0) or all (-1)
+        unsafe {
+            libc::killpg(pgid, libc::SIGTERM);
+        }
+    }
+    #[cfg(windows)]
+    {
+        let _ = Command::new("taskkill")
+            .args(["/PID", &child.id().to_string(), "/T", "/F"])
+            .output();
+    }
+
+    // Reap it so it doesn't linger as a zombie
+    std::thread::spawn(move || {
+        let _ = child.wait();
+    });
+    true
+}
+
+/// Close the login terminal: by process group when we started it ourselves,
+/// by window title for the macOS and Windows defaults we can't track
+pub fn close() {
+    if !close_tracked() {
+        close_by_title();
+    }
+}
+
+#[cfg(target_os = "macos")]
+fn close_by_title() {
+    let script = format!(
+        r#"tell application "Terminal"
+            set windowsToClose to every window whose name contains "{}"
+            repeat with w in windowsToClose
+                close w
+            end repeat
+        end tell"#,
+        WINDOW_TITLE
+    );
+    let _ = Command::new("osascript").arg("-e").arg(script).output();
+}
+
+#[cfg(target_os = "windows")]
+fn close_by_title() {
+    let _ = Command::new("taskkill")
+        .args([
+            "/FI",
+            &format!("WINDOWTITLE eq {}", WINDOW_TITLE),
+            "/T",
+            "/F",
+        ])
+        .output();
+}
+
+/// Every other platform only launches terminals it tracks
+#[cfg(not(any(target_os = "macos", target_os = "windows")))]
+fn close_by_title() {}
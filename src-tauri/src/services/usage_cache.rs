@@ -0,0 +1,34 @@
+use std::path::PathBuf;
+
+use crate::models::UsageResponse;
+use crate::services::app_dirs;
+use crate::services::settings_store::DEFAULT_PROFILE_ID;
+
+/// The default profile keeps the original `last_usage.json` name
+pub fn get_usage_cache_path(profile_id: &str) -> std::io::Result<PathBuf> {
+    let file_name = if profile_id == DEFAULT_PROFILE_ID {
+        "last_usage.json".to_string()
+    } else {
+        let safe_id: String = profile_id
+            .chars()
+            .filter(|c| c.is_ascii_alphanumeric() || *c == '-' || *c == '_')
+            .collect();
+        format!("last_usage.{}.json", safe_id)
+    };
+    Ok(app_dirs::cache_dir()?.join(file_name))
+}
+
+/// Last successfully fetched usage for the profile, if one was saved
+pub fn load_cached_usage(profile_id: &str) -> Option<UsageResponse> {
+    let content = std::fs::read_to_string(get_usage_cache_path(profile_id).ok()?).ok()?;
+    serde_json::from_str(&content).ok()
+}
+
+pub fn save_cached_usage(profile_id: &str, usage: &UsageResponse) -> Result<(), std::io::Error> {
+    let path = get_usage_cache_path(profile_id)?;
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let json = serde_json::to_string(usage)?;
+    std::fs::write(path, json)
+}
@@ -0,0 +1,298 @@
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use serde::Serialize;
+use tauri::{AppHandle, Emitter, Manager};
+use thiserror::Error;
+
+use crate::models::UsageResponse;
+use crate::services::settings_store::{self, Profile};
+use crate::services::usage_cache;
+use crate::services::{
+    fetch_usage, is_token_valid, missing_scopes, read_fresh_credentials, refresh_credentials,
+    ApiError, CredentialError, ResolvedCredentials,
+};
+
+pub const POLL_INTERVAL: Duration = Duration::from_secs(10);
+
+pub const USAGE_UPDATED_EVENT: &str = "usage-updated";
+pub const USAGE_ERROR_EVENT: &str = "usage-error";
+
+#[derive(Error, Debug)]
+pub enum UsageError {
+    #[error(transparent)]
+    Credentials(#[from] CredentialError),
+
+    #[error("Token has expired. Please login again using 'claude' CLI.")]
+    TokenExpired,
+
+    #[error("Token is missing the {} scope. Please login again using 'claude' CLI.", .0.join(", "))]
+    InsufficientScope(Vec<String>),
+
+    #[error(transparent)]
+    Api(#[from] ApiError),
+}
+
+impl UsageError {
+    pub fn code(&self) -> &'static str {
+        match self {
+            Self::Credentials(_) => "credentials",
+            Self::TokenExpired => "token_expired",
+            Self::InsufficientScope(_) => "insufficient_scope",
+            Self::Api(e) => e.code(),
+        }
+    }
+
+    pub fn retry_after(&self) -> Option<u64> {
+        match self {
+            Self::Api(e) => e.retry_after(),
+            _ => None,
+        }
+    }
+}
+
+/// Payload of the `usage-error` event
+#[derive(Debug, Clone, Serialize)]
+pub struct UsageErrorPayload {
+    pub profile_id: String,
+    pub code: String,
+    pub message: String,
+    pub retry_after: Option<u64>,
+}
+
+impl UsageErrorPayload {
+    fn new(profile_id: &str, error: &UsageError) -> Self {
+        Self {
+            profile_id: profile_id.to_string(),
+            code: error.code().to_string(),
+            message: error.to_string(),
+            retry_after: error.retry_after(),
+        }
+    }
+}
+
+/// Payload of the `usage-updated` event: the usage fields plus its profile
+#[derive(Debug, Clone, Serialize)]
+pub struct ProfileUsage {
+    pub profile_id: String,
+    #[serde(flatten)]
+    pub usage: UsageResponse,
+}
+
+/// What the last poll learned about a profile's token
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TokenStatus {
+    pub expires_at: i64,
+    /// A refresh is expected to renew the token before it expires: there is a
+    /// refresh token, the new tokens can be stored, and no refresh of this
+    /// token has failed
+    pub renewable: bool,
+}
+
+impl TokenStatus {
+    pub(crate) fn of(resolved: &ResolvedCredentials) -> Self {
+        Self {
+            expires_at: resolved.oauth.expires_at,
+            renewable: resolved.oauth.refresh_token.is_some() && resolved.provider.can_store(),
+        }
+    }
+}
+
+/// Latest usage per profile, shared by every window, the tray and any other consumer
+#[derive(Default)]
+pub struct UsageState {
+    latest: Mutex<HashMap<String, UsageResponse>>,
+    tokens: Mutex<HashMap<String, TokenStatus>>,
+}
+
+impl UsageState {
+    /// Start from the usage persisted by the previous run, if any
+    pub fn load() -> Self {
+        let cached = settings_store::load_settings_or_default()
+            .effective_profiles()
+            .into_iter()
+            .filter_map(|profile| {
+                let mut usage = usage_cache::load_cached_usage(&profile.id)?;
+                // Anything from disk is stale until the first successful fetch
+                usage.stale_since = usage.stale_since.or(usage.fetched_at);
+                Some((profile.id, usage))
+            })
+            .collect();
+        Self {
+            latest: Mutex::new(cached),
+            tokens: Mutex::default(),
+        }
+    }
+
+    /// The profile's token as of the last poll, if it could be read
+    pub fn token(&self, profile_id: &str) -> Option<TokenStatus> {
+        self.tokens
+            .lock()
+            .ok()
+            .and_then(|tokens| tokens.get(profile_id).copied())
+    }
+
+    /// Record the token a poll used. A failed refresh is remembered until the
+    /// token changes, so later polls don't make it look renewable again.
+    pub(crate) fn set_token(&self, profile_id: &str, status: Option<TokenStatus>) {
+        let Ok(mut tokens) = self.tokens.lock() else {
+            return;
+        };
+        match status {
+            Some(mut status) => {
+                if let Some(previous) = tokens.get(profile_id) {
+                    if previous.expires_at == status.expires_at && !previous.renewable {
+                        status.renewable = false;
+                    }
+                }
+                tokens.insert(profile_id.to_string(), status);
+            }
+            None => {
+                tokens.remove(profile_id);
+            }
+        }
+    }
+
+    /// Last known usage for the profile, stale or not
+    pub fn latest(&self, profile_id: &str) -> Option<UsageResponse> {
+        self.latest
+            .lock()
+            .ok()
+            .and_then(|latest| latest.get(profile_id).cloned())
+    }
+
+    /// Cached usage, if it was fetched within the last poll interval
+    pub fn fresh(&self, profile_id: &str) -> Option<UsageResponse> {
+        let now = chrono::Utc::now().timestamp_millis();
+        self.latest(profile_id).filter(|usage| {
+            usage.stale_since.is_none()
+                && usage
+                    .fetched_at
+                    .is_some_and(|fetched_at| now - fetched_at < POLL_INTERVAL.as_millis() as i64)
+        })
+    }
+
+    fn store(&self, profile_id: &str, usage: &mut UsageResponse) {
+        usage.fetched_at = Some(chrono::Utc::now().timestamp_millis());
+        usage.stale_since = None;
+        if let Ok(mut latest) = self.latest.lock() {
+            latest.insert(profile_id.to_string(), usage.clone());
+        }
+        let _ = usage_cache::save_cached_usage(profile_id, usage);
+    }
+
+    /// Flag the last known usage as stale, keeping the time it first went stale
+    fn mark_stale(&self, profile_id: &str) -> Option<UsageResponse> {
+        let mut latest = self.latest.lock().ok()?;
+        let usage = latest.get_mut(profile_id)?;
+        usage
+            .stale_since
+            .get_or_insert_with(|| chrono::Utc::now().timestamp_millis());
+        Some(usage.clone())
+    }
+}
+
+/// Fetch usage with the profile's credentials, refreshing the token when needed
+pub async fn load_usage(profile: &Profile) -> Result<UsageResponse, UsageError> {
+    load_usage_tracking(profile, &mut None).await
+}
+
+/// `load_usage`, reporting the token it used in `token`
+async fn load_usage_tracking(
+    profile: &Profile,
+    token: &mut Option<TokenStatus>,
+) -> Result<UsageResponse, UsageError> {
+    let resolved = read_fresh_credentials(profile).await?;
+    *token = Some(TokenStatus::of(&resolved));
+
+    if !is_token_valid(&resolved.oauth) {
+        return Err(UsageError::TokenExpired);
+    }
+    // Fail clearly instead of with the endpoint's bare 403
+    let missing = missing_scopes(&resolved.oauth);
+    if !missing.is_empty() {
+        return Err(UsageError::InsufficientScope(missing));
+    }
+
+    match fetch_usage(&resolved.oauth.access_token).await {
+        Ok(usage) => Ok(usage),
+        // The token can be revoked before `expires_at`; refresh once and retry
+        Err(ApiError::Unauthorized) if resolved.oauth.refresh_token.is_some() => {
+            let creds = match refresh_credentials(&resolved).await {
+                Ok(creds) => creds,
+                Err(e) => {
+                    if let Some(token) = token {
+                        token.renewable = false;
+                    }
+                    return Err(e.into());
+                }
+            };
+            *token = Some(TokenStatus {
+                expires_at: creds.expires_at,
+                ..TokenStatus::of(&resolved)
+            });
+            Ok(fetch_usage(&creds.access_token).await?)
+        }
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Fetch one profile, update the cache and notify the frontend. On failure
+/// the last known usage is re-sent with `stale_since` set before the error.
+pub async fn poll_once(app: &AppHandle, profile: &Profile) -> Result<UsageResponse, UsageError> {
+    let state = app.state::<UsageState>();
+    let mut token = None;
+    let mut result = load_usage_tracking(profile, &mut token).await;
+    state.set_token(&profile.id, token);
+
+    match &mut result {
+        Ok(usage) => {
+            state.store(&profile.id, usage);
+            emit_usage(app, &profile.id, usage.clone());
+        }
+        Err(error) => {
+            if let Some(stale) = state.mark_stale(&profile.id) {
+                emit_usage(app, &profile.id, stale);
+            }
+            let _ = app.emit(
+                USAGE_ERROR_EVENT,
+                UsageErrorPayload::new(&profile.id, error),
+            );
+        }
+    }
+
+    result
+}
+
+fn emit_usage(app: &AppHandle, profile_id: &str, usage: UsageResponse) {
+    let payload = ProfileUsage {
+        profile_id: profile_id.to_string(),
+        usage,
+    };
+    let _ = app.emit(USAGE_UPDATED_EVENT, payload);
+}
+
+/// Poll every profile and return how long to wait before the next round
+async fn poll_all(app: &AppHandle) -> Duration {
+    let mut delay = POLL_INTERVAL;
+    for profile in settings_store::load_settings_or_default().effective_profiles() {
+        // Back off for as long as the server asked when rate limited
+        if let Err(error) = poll_once(app, &profile).await {
+            if let Some(retry_after) = error.retry_after() {
+                delay = delay.max(Duration::from_secs(retry_after));
+            }
+        }
+    }
+    delay
+}
+
+/// Spawn the polling loop. Requires `UsageState` to be managed.
+pub fn start(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        loop {
+            let delay = poll_all(&app).await;
+            tokio::time::sleep(delay).await;
+        }
+    });
+}
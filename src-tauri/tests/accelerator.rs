@@ -0,0 +1,138 @@
+//! Round trips between shortcuts, their settings entries and their labels
+
+use claude_code_usage_monitor_lib::services::accelerator::{
+    self, AcceleratorError, DisplayStyle, ALL_CODES, ALL_MODIFIERS,
+};
+use claude_code_usage_monitor_lib::services::settings_store::ShortcutConfig;
+use proptest::prelude::*;
+use tauri_plugin_global_shortcut::{Code, Modifiers, Shortcut};
+
+fn config(modifier: &str, key: &str) -> ShortcutConfig {
+    ShortcutConfig {
+        modifier: modifier.to_string(),
+        key: key.to_string(),
+    }
+}
+
+fn any_shortcut() -> impl Strategy<Value = Shortcut> {
+    (
+        prop::sample::subsequence(ALL_MODIFIERS.to_vec(), 0..=ALL_MODIFIERS.len()),
+        prop::sample::select(ALL_CODES),
+    )
+        .prop_map(|(mods, code)| {
+            let mods = mods.into_iter().fold(Modifiers::empty(), |all, m| all | m);
+            Shortcut::new(Some(mods), code)
+        })
+}
+
+fn any_style() -> impl Strategy<Value = DisplayStyle> {
+    prop::sample::select(vec![
+        DisplayStyle::Glyphs,
+        DisplayStyle::Windows,
+        DisplayStyle::Text,
+    ])
+}
+
+proptest! {
+    #[test]
+    fn labels_parse_back_to_the_same_shortcut(shortcut in any_shortcut(), style in any_style()) {
+        let label = accelerator::format_shortcut(&shortcut, style);
+        prop_assert_eq!(accelerator::parse_display(&label), Ok(shortcut), "{}", label);
+    }
+
+    #[test]
+    fn settings_entries_parse_back_to_the_same_shortcut(shortcut in any_shortcut()) {
+        let config = accelerator::to_config(&shortcut);
+        prop_assert_eq!(accelerator::parse_config(&config), Ok(shortcut));
+    }
+
+    #[test]
+    fn displayed_settings_parse_back_to_the_entry(shortcut in any_shortcut()) {
+        let config = accelerator::to_config(&shortcut);
+        let label = accelerator::format_shortcut_display(&config);
+        let parsed = accelerator::parse_display(&label).unwrap();
+        let round_tripped = accelerator::to_config(&parsed);
+        prop_assert_eq!(round_tripped.modifier, config.modifier);
+        prop_assert_eq!(round_tripped.key, config.key);
+    }
+
+    #[test]
+    fn key_names_ignore_case(code in prop::sample::select(ALL_CODES), upper in any::<bool>()) {
+        let name = code.to_string();
+        let name = if upper { name.to_uppercase() } else { name.to_lowercase() };
+        prop_assert_eq!(accelerator::parse_key(&name), Ok(code));
+    }
+}
+
+#[test]
+fn accepts_entries_written_by_older_versions() {
+    for (modifier, mods) in [
+        ("alt", Modifiers::ALT),
+        ("control", Modifiers::CONTROL),
+        ("cmd", Modifiers::SUPER),
+        ("meta", Modifiers::SUPER),
+        ("ctrl+shift", Modifiers::CONTROL | Modifiers::SHIFT),
+        ("cmd+shift", Modifiers::SUPER | Modifiers::SHIFT),
+        ("alt+shift", Modifiers::ALT | Modifiers::SHIFT),
+    ] {
+        assert_eq!(
+            accelerator::parse_config(&config(modifier, "u")),
+            Ok(Shortcut::new(Some(mods), Code::KeyU)),
+            "{}",
+            modifier
+        );
+    }
+}
+
+#[test]
+fn accepts_keys_beyond_letters() {
+    let all = Modifiers::CONTROL | Modifiers::ALT | Modifiers::SHIFT;
+    assert_eq!(
+        accelerator::parse_config(&config("ctrl+alt+shift", "f5")),
+        Ok(Shortcut::new(Some(all), Code::F5))
+    );
+    for (key, code) in [
+        ("7", Code::Digit7),
+        ("/", Code::Slash),
+        ("space", Code::Space),
+        ("up", Code::ArrowUp),
+        ("ArrowUp", Code::ArrowUp),
+        ("esc", Code::Escape),
+        ("numpadadd", Code::NumpadAdd),
+    ] {
+        assert_eq!(accelerator::parse_key(key), Ok(code), "{}", key);
+    }
+}
+
+#[test]
+fn formats_platform_labels() {
+    let shortcut = Shortcut::new(Some(Modifiers::SUPER | Modifiers::SHIFT), Code::KeyU);
+    assert_eq!(
+        accelerator::format_shortcut(&shortcut, DisplayStyle::Glyphs),
+        "⇧⌘U"
+    );
+    assert_eq!(
+        accelerator::format_shortcut(&shortcut, DisplayStyle::Windows),
+        "Win+Shift+U"
+    );
+    assert_eq!(
+        accelerator::format_shortcut(&shortcut, DisplayStyle::Text),
+        "Super+Shift+U"
+    );
+}
+
+#[test]
+fn reports_what_it_could_not_parse() {
+    assert_eq!(
+        accelerator::parse_config(&config("ctrl+hyperdrive", "u")),
+        Err(AcceleratorError::UnknownModifier("hyperdrive".to_string()))
+    );
+    assert_eq!(
+        accelerator::parse_config(&config("ctrl", "nope")),
+        Err(AcceleratorError::UnknownKey("nope".to_string()))
+    );
+    assert_eq!(
+        accelerator::parse_config(&config("ctrl", " ")),
+        Err(AcceleratorError::MissingKey)
+    );
+}
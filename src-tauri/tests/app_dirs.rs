@@ -0,0 +1,61 @@
+//! Where app files live, and moving them out of the pre-XDG folder
+
+mod common;
+
+use claude_code_usage_monitor_lib::services::{app_dirs, diagnostics, settings_store, usage_cache};
+use common::{TestEnv, USAGE_BODY};
+
+#[tokio::test]
+async fn moves_files_out_of_the_legacy_folder() {
+    let env = TestEnv::new().await;
+    let home = env.home.clone();
+    if cfg!(target_os = "linux") {
+        std::env::set_var("XDG_CONFIG_HOME", home.join("xdg-config"));
+    }
+
+    let legacy = app_dirs::legacy_dir().unwrap();
+    std::fs::create_dir_all(&legacy).unwrap();
+    std::fs::write(
+        legacy.join("settings.json"),
+        r#"{"schema_version": 1, "shortcut": {"modifier": "alt", "key": "k"}, "first_launch": false}"#,
+    )
+    .unwrap();
+    std::fs::write(legacy.join("settings.json.bak.1"), "{}").unwrap();
+    std::fs::write(legacy.join("last_usage.json"), USAGE_BODY).unwrap();
+    std::fs::write(legacy.join("diagnostics.log"), "earlier\n").unwrap();
+    std::fs::write(legacy.join("notes.txt"), "not ours").unwrap();
+
+    let moved = app_dirs::migrate_legacy_dir().unwrap();
+
+    if cfg!(target_os = "linux") {
+        assert_eq!(moved, 4);
+        assert_eq!(
+            settings_store::get_settings_path().unwrap(),
+            home.join("xdg-config/claude-usage-monitor/settings.json")
+        );
+        assert_eq!(
+            usage_cache::get_usage_cache_path("default").unwrap(),
+            home.join(".cache/claude-usage-monitor/last_usage.json")
+        );
+        assert_eq!(
+            diagnostics::get_diagnostics_path().unwrap(),
+            home.join(".local/share/claude-usage-monitor/diagnostics.log")
+        );
+        assert!(settings_store::get_settings_path()
+            .unwrap()
+            .with_extension("json.bak.1")
+            .exists());
+        // Files we don't own keep the old folder around
+        assert!(legacy.join("notes.txt").exists());
+        assert!(!legacy.join("settings.json").exists());
+    } else {
+        assert_eq!(moved, 0);
+    }
+    let settings = settings_store::load_settings().unwrap();
+    assert_eq!(settings.shortcut.key, "k");
+    assert!(!settings.first_launch);
+    assert!(usage_cache::load_cached_usage("default").is_some());
+
+    // A second run finds nothing left to move
+    assert_eq!(app_dirs::migrate_legacy_dir().unwrap(), 0);
+}
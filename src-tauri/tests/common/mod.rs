@@ -0,0 +1,244 @@
+//! Shared setup for the integration tests: a throwaway `HOME` per test and a
+//! local HTTP stand-in for the Anthropic endpoints.
+//!
+//! Tests change process-wide environment variables, so `TestEnv` holds a lock
+//! for as long as it lives.
+
+// Each test file uses a different subset
+#![allow(dead_code)]
+
+use std::collections::VecDeque;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+
+use claude_code_usage_monitor_lib::services::settings_store::{self, Profile};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpListener;
+use tokio::sync::MutexGuard;
+
+static ENV_LOCK: tokio::sync::Mutex<()> = tokio::sync::Mutex::const_new(());
+static TEST_ID: AtomicUsize = AtomicUsize::new(0);
+
+pub const HOUR_MS: i64 = 60 * 60 * 1000;
+
+pub const USAGE_BODY: &str = r#"{
+    "five_hour": {"utilization": 12.0, "resets_at": "2030-01-01T00:00:00Z"},
+    "seven_day": {"utilization": 34.5, "resets_at": null},
+    "seven_day_opus": null,
+    "seven_day_sonnet": {"utilization": 5.0, "resets_at": null}
+}"#;
+
+/// A fresh `HOME` with the app's environment overrides cleared, removed again
+/// when dropped
+pub struct TestEnv {
+    pub home: PathBuf,
+    _lock: MutexGuard<'static, ()>,
+}
+
+impl TestEnv {
+    pub async fn new() -> Self {
+        let lock = ENV_LOCK.lock().await;
+        let home = std::env::temp_dir().join(format!(
+            "usage-monitor-test-{}-{}",
+            std::process::id(),
+            TEST_ID.fetch_add(1, Ordering::SeqCst)
+        ));
+        std::fs::create_dir_all(&home).unwrap();
+
+        std::env::set_var("HOME", &home);
+        for var in [
+            "CLAUDE_CODE_OAUTH_TOKEN",
+            "CLAUDE_CONFIG_DIR",
+            "CLAUDE_USAGE_MONITOR_API_BASE_URL",
+            "CLAUDE_USAGE_MONITOR_TOKEN_ENDPOINT",
+            "CLAUDE_USAGE_MONITOR_AUTHORIZE_ENDPOINT",
+            // Never the developer's own keyring
+            "DBUS_SESSION_BUS_ADDRESS",
+            // App files then live under the test's `HOME` on Linux too
+            "XDG_CONFIG_HOME",
+            "XDG_CACHE_HOME",
+            "XDG_DATA_HOME",
+        ] {
+            std::env::remove_var(var);
+        }
+        // Retries happen straight away
+        std::env::set_var("CLAUDE_USAGE_MONITOR_RETRY_BACKOFF_MS", "0");
+
+        Self { home, _lock: lock }
+    }
+
+    /// `new` with a credentials file holding a token that expires in
+    /// `expires_in_ms`; returns the file's path
+    pub async fn with_credentials(
+        expires_in_ms: i64,
+        refresh_token: Option<&str>,
+    ) -> (Self, PathBuf) {
+        let env = Self::new().await;
+        let path = env.write_credentials(expires_in_ms, refresh_token);
+        (env, path)
+    }
+
+    /// Write `~/.claude/.credentials.json` and return its path
+    pub fn write_credentials(&self, expires_in_ms: i64, refresh_token: Option<&str>) -> PathBuf {
+        let claude_dir = self.home.join(".claude");
+        std::fs::create_dir_all(&claude_dir).unwrap();
+
+        let mut oauth = serde_json::json!({
+            "accessToken": "test-access-token",
+            "expiresAt": chrono::Utc::now().timestamp_millis() + expires_in_ms,
+            "scopes": ["user:inference", "user:profile"],
+        });
+        if let Some(token) = refresh_token {
+            oauth["refreshToken"] = token.into();
+        }
+        let creds = serde_json::json!({ "claudeAiOauth": oauth });
+        let path = claude_dir.join(".credentials.json");
+        std::fs::write(&path, serde_json::to_string(&creds).unwrap()).unwrap();
+        path
+    }
+
+    /// Send API and OAuth requests to `server`
+    pub fn use_server(&self, server: &MockServer) {
+        std::env::set_var("CLAUDE_USAGE_MONITOR_API_BASE_URL", &server.base_url);
+        std::env::set_var(
+            "CLAUDE_USAGE_MONITOR_TOKEN_ENDPOINT",
+            format!("{}/v1/oauth/token", server.base_url),
+        );
+        std::env::set_var(
+            "CLAUDE_USAGE_MONITOR_AUTHORIZE_ENDPOINT",
+            format!("{}/oauth/authorize", server.base_url),
+        );
+    }
+}
+
+impl Drop for TestEnv {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.home);
+    }
+}
+
+pub fn active_profile() -> Profile {
+    settings_store::load_settings()
+        .unwrap()
+        .profile(None)
+        .unwrap()
+}
+
+pub struct CannedResponse {
+    pub status: u16,
+    pub headers: Vec<(&'static str, String)>,
+    pub body: String,
+}
+
+pub fn respond(status: u16, body: &str) -> CannedResponse {
+    CannedResponse {
+        status,
+        headers: Vec::new(),
+        body: body.to_string(),
+    }
+}
+
+/// Serves queued responses in order and records the request paths it saw
+pub struct MockServer {
+    pub base_url: String,
+    requests: Arc<Mutex<Vec<String>>>,
+}
+
+impl MockServer {
+    pub async fn start(responses: Vec<CannedResponse>) -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let base_url = format!("http://{}", listener.local_addr().unwrap());
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let queue = Arc::new(Mutex::new(VecDeque::from(responses)));
+
+        let seen = requests.clone();
+        tokio::spawn(async move {
+            loop {
+                let Ok((mut stream, _)) = listener.accept().await else {
+                    return;
+                };
+                let seen = seen.clone();
+                let queue = queue.clone();
+                tokio::spawn(async move {
+                    loop {
+                        let Some(path) = read_request(&mut stream).await else {
+                            return;
+                        };
+                        seen.lock().unwrap().push(path);
+                        let canned = queue
+                            .lock()
+                            .unwrap()
+                            .pop_front()
+                            .unwrap_or_else(|| respond(500, "queue exhausted"));
+                        if stream.write_all(&encode(&canned)).await.is_err() {
+                            return;
+                        }
+                    }
+                });
+            }
+        });
+
+        Self { base_url, requests }
+    }
+
+    /// `start`, with `env` sending its requests here
+    pub async fn serve(env: &TestEnv, responses: Vec<CannedResponse>) -> Self {
+        let server = Self::start(responses).await;
+        env.use_server(&server);
+        server
+    }
+
+    pub fn requests(&self) -> Vec<String> {
+        self.requests.lock().unwrap().clone()
+    }
+}
+
+/// Read one HTTP/1.1 request and return its path
+async fn read_request(stream: &mut tokio::net::TcpStream) -> Option<String> {
+    let mut buf = Vec::new();
+    let mut chunk = [0u8; 4096];
+
+    let header_end = loop {
+        if let Some(pos) = buf.windows(4).position(|w| w == b"\r\n\r\n") {
+            break pos + 4;
+        }
+        let n = stream.read(&mut chunk).await.ok()?;
+        if n == 0 {
+            return None;
+        }
+        buf.extend_from_slice(&chunk[..n]);
+    };
+
+    let head = String::from_utf8_lossy(&buf[..header_end]).to_string();
+    let content_length = head
+        .lines()
+        .filter_map(|line| line.split_once(':'))
+        .find(|(name, _)| name.eq_ignore_ascii_case("content-length"))
+        .and_then(|(_, value)| value.trim().parse::<usize>().ok())
+        .unwrap_or(0);
+
+    while buf.len() < header_end + content_length {
+        let n = stream.read(&mut chunk).await.ok()?;
+        if n == 0 {
+            return None;
+        }
+        buf.extend_from_slice(&chunk[..n]);
+    }
+
+    head.split_whitespace().nth(1).map(str::to_string)
+}
+
+fn encode(canned: &CannedResponse) -> Vec<u8> {
+    let mut response = format!(
+        "HTTP/1.1 {} Mock\r\nContent-Type: application/json\r\nContent-Length: {}\r\n",
+        canned.status,
+        canned.body.len()
+    );
+    for (name, value) in &canned.headers {
+        response.push_str(&format!("{}: {}\r\n", name, value));
+    }
+    response.push_str("\r\n");
+    response.push_str(&canned.body);
+    response.into_bytes()
+}
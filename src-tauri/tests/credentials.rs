@@ -0,0 +1,238 @@
+//! Resolving credentials through the provider chain, refreshing them and
+//! reporting the result through `check_credentials`
+
+mod common;
+
+use std::path::PathBuf;
+
+use claude_code_usage_monitor_lib::commands::check_credentials;
+use claude_code_usage_monitor_lib::services::expiry_monitor;
+use claude_code_usage_monitor_lib::services::settings_store::{self, CredentialSource, Profile};
+use claude_code_usage_monitor_lib::services::usage_scheduler::load_usage;
+use common::{active_profile, respond, MockServer, TestEnv, HOUR_MS, USAGE_BODY};
+
+#[tokio::test]
+async fn check_credentials_reports_valid_token() {
+    let (env, path) = TestEnv::with_credentials(HOUR_MS, Some("refresh")).await;
+    let server = MockServer::serve(&env, Vec::new()).await;
+
+    let status = check_credentials(None).await.unwrap();
+
+    assert!(status.authenticated);
+    assert_eq!(status.error_reason, None);
+    assert_eq!(status.credentials_path, path.display().to_string());
+    assert_eq!(status.source.as_deref(), Some("default_file"));
+    let skipped: Vec<_> = status
+        .skipped_providers
+        .iter()
+        .map(|attempt| attempt.provider.as_str())
+        .collect();
+    assert_eq!(skipped, vec!["env"]);
+    assert!(server.requests().is_empty());
+}
+
+#[tokio::test]
+async fn check_credentials_refreshes_expired_token() {
+    let (env, _) = TestEnv::with_credentials(-HOUR_MS, Some("refresh")).await;
+    let token = r#"{"access_token":"new-token","expires_in":3600}"#;
+    let server = MockServer::serve(&env, vec![respond(200, token)]).await;
+
+    let status = check_credentials(None).await.unwrap();
+
+    assert!(status.authenticated);
+    assert_eq!(server.requests(), vec!["/v1/oauth/token"]);
+}
+
+#[tokio::test]
+async fn refreshes_a_token_shortly_before_it_expires() {
+    let (env, _) = TestEnv::with_credentials(2 * 60 * 1000, Some("refresh")).await;
+    let token = r#"{"access_token":"new-token","expires_in":3600}"#;
+    let server = MockServer::serve(&env, vec![respond(200, token)]).await;
+
+    let status = check_credentials(None).await.unwrap();
+
+    assert!(status.authenticated);
+    assert!(status.expires_at.unwrap() > chrono::Utc::now().timestamp_millis() + HOUR_MS / 2);
+    assert_eq!(server.requests(), vec!["/v1/oauth/token"]);
+}
+
+#[tokio::test]
+async fn keeps_a_valid_token_when_an_early_refresh_fails() {
+    let (env, _) = TestEnv::with_credentials(2 * 60 * 1000, Some("revoked")).await;
+    MockServer::serve(&env, vec![respond(400, r#"{"error":"invalid_grant"}"#)]).await;
+
+    let status = check_credentials(None).await.unwrap();
+
+    assert!(status.authenticated);
+    assert_eq!(status.error_reason, None);
+}
+
+#[tokio::test]
+async fn renewing_ahead_of_a_warning_finds_a_revoked_refresh_token() {
+    let (env, _) = TestEnv::with_credentials(20 * 60 * 1000, Some("revoked")).await;
+    let server = MockServer::serve(&env, vec![respond(400, r#"{"error":"invalid_grant"}"#)]).await;
+    let expires_at = check_credentials(None).await.unwrap().expires_at.unwrap();
+    assert!(server.requests().is_empty());
+
+    let renewed = expiry_monitor::renew_before_expiry(&active_profile(), expires_at).await;
+
+    assert!(renewed.is_err());
+    assert_eq!(server.requests(), vec!["/v1/oauth/token"]);
+}
+
+#[tokio::test]
+async fn check_credentials_reports_failed_refresh() {
+    let (env, _) = TestEnv::with_credentials(-HOUR_MS, Some("revoked")).await;
+    MockServer::serve(&env, vec![respond(400, r#"{"error":"invalid_grant"}"#)]).await;
+
+    let status = check_credentials(None).await.unwrap();
+
+    assert!(!status.authenticated);
+    assert_eq!(status.error_reason.as_deref(), Some("refresh_failed"));
+}
+
+#[tokio::test]
+async fn does_not_retry_a_refresh_the_server_received() {
+    let (env, _) = TestEnv::with_credentials(-HOUR_MS, Some("refresh")).await;
+    let server = MockServer::serve(&env, vec![respond(503, "busy"), respond(503, "busy")]).await;
+
+    let status = check_credentials(None).await.unwrap();
+
+    assert!(!status.authenticated);
+    assert_eq!(status.error_reason.as_deref(), Some("refresh_failed"));
+    assert_eq!(server.requests(), vec!["/v1/oauth/token"]);
+}
+
+#[tokio::test]
+async fn check_credentials_reports_missing_file() {
+    let _env = TestEnv::new().await;
+
+    let status = check_credentials(None).await.unwrap();
+
+    assert!(!status.authenticated);
+    assert_eq!(status.error_reason.as_deref(), Some("not_found"));
+    assert_eq!(status.source, None);
+    assert_eq!(status.skipped_providers.len(), 3);
+}
+
+#[tokio::test]
+async fn check_credentials_prefers_env_token() {
+    let _env = TestEnv::with_credentials(-HOUR_MS, None).await;
+    std::env::set_var("CLAUDE_CODE_OAUTH_TOKEN", "env-token");
+
+    let status = check_credentials(None).await.unwrap();
+
+    assert!(status.authenticated);
+    assert_eq!(status.source.as_deref(), Some("env"));
+    assert_eq!(status.expires_at, None);
+    assert!(status.skipped_providers.is_empty());
+}
+
+#[tokio::test]
+async fn check_credentials_follows_claude_config_dir() {
+    let (env, default_path) = TestEnv::with_credentials(HOUR_MS, None).await;
+    let config_dir = env.home.join("custom-claude");
+    std::fs::create_dir_all(&config_dir).unwrap();
+    let custom_path = config_dir.join(".credentials.json");
+    std::fs::rename(&default_path, &custom_path).unwrap();
+    std::env::set_var("CLAUDE_CONFIG_DIR", &config_dir);
+
+    let status = check_credentials(None).await.unwrap();
+
+    assert!(status.authenticated);
+    assert_eq!(status.credentials_path, custom_path.display().to_string());
+}
+
+#[tokio::test]
+async fn profiles_use_their_own_credentials() {
+    let (env, personal_path) = TestEnv::with_credentials(HOUR_MS, None).await;
+    MockServer::serve(&env, vec![respond(200, USAGE_BODY)]).await;
+    let work_path = personal_path.with_file_name("work.json");
+    std::fs::write(
+        &work_path,
+        r#"{"claudeAiOauth":{"accessToken":"work-token","expiresAt":1}}"#,
+    )
+    .unwrap();
+
+    let file = |path: &PathBuf| CredentialSource::File {
+        path: path.display().to_string(),
+    };
+    let mut settings = settings_store::load_settings().unwrap();
+    settings.profiles = vec![
+        Profile {
+            id: "personal".to_string(),
+            name: "Personal".to_string(),
+            credential_providers: vec![file(&personal_path)],
+        },
+        Profile {
+            id: "work".to_string(),
+            name: "Work".to_string(),
+            credential_providers: vec![file(&work_path)],
+        },
+    ];
+    settings.active_profile = Some("work".to_string());
+    settings_store::save_settings(&settings).unwrap();
+
+    let work = check_credentials(None).await.unwrap();
+    assert_eq!(work.profile_id, "work");
+    assert!(!work.authenticated);
+    assert_eq!(work.credentials_path, work_path.display().to_string());
+
+    let personal = check_credentials(Some("personal".to_string()))
+        .await
+        .unwrap();
+    assert_eq!(personal.profile_id, "personal");
+    assert!(personal.authenticated);
+
+    let profile = settings.profile(Some("personal")).unwrap();
+    assert!(load_usage(&profile).await.is_ok());
+    assert!(check_credentials(Some("missing".to_string()))
+        .await
+        .is_err());
+}
+
+#[tokio::test]
+async fn reports_insufficient_scope_without_calling_the_api() {
+    let env = TestEnv::new().await;
+    let server = MockServer::serve(&env, Vec::new()).await;
+    let path = env.write_credentials(HOUR_MS, None);
+    let expires_at = chrono::Utc::now().timestamp_millis() + HOUR_MS;
+    std::fs::write(
+        &path,
+        format!(
+            r#"{{"claudeAiOauth":{{"accessToken":"t","expiresAt":{},"scopes":["user:inference"]}}}}"#,
+            expires_at
+        ),
+    )
+    .unwrap();
+
+    let status = check_credentials(None).await.unwrap();
+    assert!(!status.authenticated);
+    assert_eq!(status.error_reason.as_deref(), Some("insufficient_scope"));
+    assert_eq!(status.missing_scopes, vec!["user:profile"]);
+
+    let error = load_usage(&active_profile()).await.unwrap_err();
+    assert_eq!(error.code(), "insufficient_scope");
+    assert!(server.requests().is_empty());
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn warns_about_and_fixes_readable_credentials_file() {
+    use claude_code_usage_monitor_lib::commands::fix_credentials_permissions;
+    use std::os::unix::fs::PermissionsExt;
+
+    let (_env, path) = TestEnv::with_credentials(HOUR_MS, None).await;
+    std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o644)).unwrap();
+
+    let status = check_credentials(None).await.unwrap();
+    let warning = status.permission_warning.unwrap();
+    assert_eq!(warning.reason, "world_readable");
+    assert_eq!(warning.mode, "0644");
+    assert!(warning.fixable);
+
+    let status = fix_credentials_permissions(None).await.unwrap();
+    assert!(status.permission_warning.is_none());
+    let mode = std::fs::metadata(&path).unwrap().permissions().mode() & 0o777;
+    assert_eq!(mode, 0o600);
+}
@@ -0,0 +1,27 @@
+//! The step-by-step connection test against a local server
+
+mod common;
+
+use claude_code_usage_monitor_lib::services::network::{test_connection, CheckStatus};
+use common::{respond, MockServer, TestEnv};
+
+#[tokio::test]
+async fn skips_tls_for_plain_http_targets() {
+    let env = TestEnv::new().await;
+    let server = MockServer::serve(&env, vec![respond(404, "{}")]).await;
+
+    let report = test_connection(&server.base_url).await;
+
+    let status = |stage: &str| {
+        report
+            .checks
+            .iter()
+            .find(|check| check.stage == stage)
+            .map(|check| check.status)
+    };
+    assert!(report.ok);
+    assert_eq!(status("dns"), Some(CheckStatus::Ok));
+    assert_eq!(status("proxy"), Some(CheckStatus::Skipped));
+    assert_eq!(status("tls"), Some(CheckStatus::Skipped));
+    assert_eq!(status("http"), Some(CheckStatus::Ok));
+}
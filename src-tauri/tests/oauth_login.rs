@@ -0,0 +1,115 @@
+//! The in-app PKCE login, with a stand-in browser that follows the
+//! authorization URL straight back to the redirect URI
+
+mod common;
+
+use std::sync::{Arc, Mutex};
+
+use claude_code_usage_monitor_lib::commands::check_credentials;
+use claude_code_usage_monitor_lib::services::oauth_login;
+use common::{active_profile, respond, MockServer, TestEnv};
+
+type Statuses = Arc<Mutex<Vec<u16>>>;
+
+/// Stands in for the browser: opens a connection it never uses, sends the
+/// redirect with each of the `forged` states, then the real one. Returns the
+/// status each redirect got, in order.
+fn fake_browser(
+    server_base: String,
+    code: &'static str,
+    forged: &'static [&'static str],
+) -> (impl FnOnce(&str) -> Result<(), String>, Statuses) {
+    let statuses = Statuses::default();
+    let seen = statuses.clone();
+    let browser = move |url: &str| {
+        let url = reqwest::Url::parse(url).unwrap();
+        assert_eq!(
+            url.as_str().split('?').next().unwrap(),
+            format!("{}/oauth/authorize", server_base)
+        );
+        let param = |name: &str| {
+            url.query_pairs()
+                .find(|(key, _)| key == name)
+                .map(|(_, value)| value.into_owned())
+                .unwrap()
+        };
+        assert_eq!(param("code_challenge_method"), "S256");
+        assert_eq!(param("code_challenge").len(), 43);
+
+        let redirect_uri = param("redirect_uri");
+        let port = reqwest::Url::parse(&redirect_uri).unwrap().port().unwrap();
+        let states: Vec<String> = forged
+            .iter()
+            .map(|state| state.to_string())
+            .chain([param("state")])
+            .collect();
+        tokio::spawn(async move {
+            let _idle = tokio::net::TcpStream::connect(("127.0.0.1", port))
+                .await
+                .unwrap();
+            for state in states {
+                let callback = format!("{}?code={}&state={}", redirect_uri, code, state);
+                let status = reqwest::get(callback).await.unwrap().status().as_u16();
+                seen.lock().unwrap().push(status);
+            }
+        });
+        Ok(())
+    };
+    (browser, statuses)
+}
+
+/// The statuses once the browser has read `count` responses; the login can
+/// return before the browser reads the last page
+async fn wait_for_statuses(statuses: &Statuses, count: usize) -> Vec<u16> {
+    for _ in 0..250 {
+        if statuses.lock().unwrap().len() >= count {
+            break;
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+    }
+    statuses.lock().unwrap().clone()
+}
+
+#[tokio::test]
+async fn logs_in_through_the_browser_and_stores_tokens() {
+    let env = TestEnv::new().await;
+    let token = r#"{"access_token":"login-token","refresh_token":"login-refresh","expires_in":3600,"scope":"user:inference user:profile"}"#;
+    let server = MockServer::serve(&env, vec![respond(200, token)]).await;
+    let path = env.home.join(".claude/.credentials.json");
+
+    let (browser, statuses) = fake_browser(server.base_url.clone(), "auth-code", &[]);
+    let oauth = oauth_login::login(&active_profile(), browser)
+        .await
+        .unwrap();
+
+    assert_eq!(oauth.access_token.expose(), "login-token");
+    assert_eq!(server.requests(), vec!["/v1/oauth/token"]);
+    let saved: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+    assert_eq!(saved["claudeAiOauth"]["accessToken"], "login-token");
+    assert_eq!(saved["claudeAiOauth"]["refreshToken"], "login-refresh");
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let mode = std::fs::metadata(&path).unwrap().permissions().mode() & 0o777;
+        assert_eq!(mode, 0o600);
+    }
+    assert!(check_credentials(None).await.unwrap().authenticated);
+    assert_eq!(wait_for_statuses(&statuses, 1).await, vec![200]);
+}
+
+#[tokio::test]
+async fn turns_away_redirects_with_the_wrong_state_and_keeps_waiting() {
+    let env = TestEnv::new().await;
+    let token = r#"{"access_token":"login-token","expires_in":3600}"#;
+    let server = MockServer::serve(&env, vec![respond(200, token)]).await;
+
+    let (browser, statuses) = fake_browser(server.base_url.clone(), "auth-code", &["forged", ""]);
+    let oauth = oauth_login::login(&active_profile(), browser)
+        .await
+        .unwrap();
+
+    assert_eq!(oauth.access_token.expose(), "login-token");
+    assert_eq!(server.requests(), vec!["/v1/oauth/token"]);
+    assert_eq!(wait_for_statuses(&statuses, 3).await, vec![400, 400, 200]);
+}
@@ -0,0 +1,385 @@
+//! The Secret Service provider against a stand-in keyring on a private
+//! session bus, so the D-Bus calls, item attributes and unlock prompts run
+//! without touching a real keyring. Skipped where `dbus-daemon` isn't
+//! installed.
+
+#![cfg(target_os = "linux")]
+
+mod common;
+
+use std::collections::HashMap;
+use std::io::{BufRead, BufReader};
+use std::process::{Child, Command, Stdio};
+use std::sync::{Arc, Mutex};
+
+use claude_code_usage_monitor_lib::commands::check_credentials;
+use claude_code_usage_monitor_lib::services::settings_store::{self, CredentialSource};
+use common::{respond, MockServer, TestEnv, HOUR_MS};
+use serde::{Deserialize, Serialize};
+use zbus::object_server::SignalEmitter;
+use zbus::zvariant::{ObjectPath, OwnedObjectPath, OwnedValue, Type, Value};
+use zbus::{fdo, interface, Connection, ObjectServer};
+
+const SERVICE_PATH: &str = "/org/freedesktop/secrets";
+const COLLECTION_PATH: &str = "/org/freedesktop/secrets/collection/login";
+const SESSION_PATH: &str = "/org/freedesktop/secrets/session/1";
+
+/// A `dbus-daemon` of our own, stopped when dropped
+struct PrivateBus {
+    daemon: Child,
+    address: String,
+}
+
+impl PrivateBus {
+    fn start() -> Option<Self> {
+        let mut daemon = Command::new("dbus-daemon")
+            .args(["--session", "--nofork", "--print-address"])
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()
+            .ok()?;
+        let address = daemon.stdout.take().and_then(|stdout| {
+            let mut line = String::new();
+            BufReader::new(stdout).read_line(&mut line).ok()?;
+            Some(line.trim().to_string()).filter(|line| !line.is_empty())
+        });
+        let Some(address) = address else {
+            let _ = daemon.kill();
+            let _ = daemon.wait();
+            return None;
+        };
+        Some(Self { daemon, address })
+    }
+}
+
+impl Drop for PrivateBus {
+    fn drop(&mut self) {
+        let _ = self.daemon.kill();
+        let _ = self.daemon.wait();
+    }
+}
+
+struct StoredItem {
+    attributes: HashMap<String, String>,
+    secret: Vec<u8>,
+}
+
+/// What the stand-in keyring holds and how it answers prompts
+#[derive(Default)]
+struct Keyring {
+    items: Vec<StoredItem>,
+    locked: bool,
+    /// Unlock prompts are dismissed instead of accepted
+    dismiss_prompts: bool,
+    prompts_shown: usize,
+}
+
+type Shared = Arc<Mutex<Keyring>>;
+
+#[derive(Serialize, Deserialize, Type)]
+struct Secret {
+    session: OwnedObjectPath,
+    parameters: Vec<u8>,
+    value: Vec<u8>,
+    content_type: String,
+}
+
+#[derive(Debug, zbus::DBusError)]
+#[zbus(prefix = "org.freedesktop.Secret.Error")]
+enum SecretError {
+    #[zbus(error)]
+    ZBus(zbus::Error),
+    IsLocked(String),
+}
+
+fn object_path(path: &str) -> OwnedObjectPath {
+    OwnedObjectPath::try_from(path).unwrap()
+}
+
+fn item_path(index: usize) -> OwnedObjectPath {
+    object_path(&format!("{}/{}", COLLECTION_PATH, index))
+}
+
+struct Service {
+    keyring: Shared,
+}
+
+#[interface(name = "org.freedesktop.Secret.Service")]
+impl Service {
+    fn open_session(
+        &self,
+        algorithm: &str,
+        _input: OwnedValue,
+    ) -> fdo::Result<(Value<'static>, OwnedObjectPath)> {
+        if algorithm != "plain" {
+            return Err(fdo::Error::NotSupported(algorithm.to_string()));
+        }
+        Ok((Value::from(""), object_path(SESSION_PATH)))
+    }
+
+    fn search_items(
+        &self,
+        attributes: HashMap<String, String>,
+    ) -> (Vec<OwnedObjectPath>, Vec<OwnedObjectPath>) {
+        let keyring = self.keyring.lock().unwrap();
+        let found: Vec<OwnedObjectPath> = keyring
+            .items
+            .iter()
+            .enumerate()
+            .filter(|(_, item)| {
+                attributes
+                    .iter()
+                    .all(|(key, value)| item.attributes.get(key) == Some(value))
+            })
+            .map(|(index, _)| item_path(index))
+            .collect();
+        if keyring.locked {
+            (Vec::new(), found)
+        } else {
+            (found, Vec::new())
+        }
+    }
+
+    async fn unlock(
+        &self,
+        objects: Vec<OwnedObjectPath>,
+        #[zbus(object_server)] server: &ObjectServer,
+    ) -> fdo::Result<(Vec<OwnedObjectPath>, OwnedObjectPath)> {
+        let number = {
+            let mut keyring = self.keyring.lock().unwrap();
+            if !keyring.locked {
+                return Ok((objects, object_path("/")));
+            }
+            keyring.prompts_shown += 1;
+            keyring.prompts_shown
+        };
+        let prompt = object_path(&format!("{}/prompt/{}", SERVICE_PATH, number));
+        let stand_in = Prompt {
+            keyring: self.keyring.clone(),
+            objects,
+        };
+        server.at(prompt.clone(), stand_in).await?;
+        Ok((Vec::new(), prompt))
+    }
+}
+
+struct Prompt {
+    keyring: Shared,
+    objects: Vec<OwnedObjectPath>,
+}
+
+#[interface(name = "org.freedesktop.Secret.Prompt")]
+impl Prompt {
+    async fn prompt(
+        &self,
+        _window_id: &str,
+        #[zbus(signal_emitter)] emitter: SignalEmitter<'_>,
+    ) -> fdo::Result<()> {
+        let dismissed = {
+            let mut keyring = self.keyring.lock().unwrap();
+            if !keyring.dismiss_prompts {
+                keyring.locked = false;
+            }
+            keyring.dismiss_prompts
+        };
+        let unlocked: Vec<ObjectPath<'static>> = if dismissed {
+            Vec::new()
+        } else {
+            self.objects
+                .iter()
+                .map(|path| path.clone().into())
+                .collect()
+        };
+        Self::completed(&emitter, dismissed, Value::from(unlocked)).await?;
+        Ok(())
+    }
+
+    #[zbus(signal)]
+    async fn completed(
+        emitter: &SignalEmitter<'_>,
+        dismissed: bool,
+        result: Value<'_>,
+    ) -> zbus::Result<()>;
+}
+
+struct Session;
+
+#[interface(name = "org.freedesktop.Secret.Session")]
+impl Session {
+    fn close(&self) {}
+}
+
+struct Item {
+    keyring: Shared,
+    index: usize,
+}
+
+#[interface(name = "org.freedesktop.Secret.Item")]
+impl Item {
+    fn get_secret(&self, session: OwnedObjectPath) -> Result<(Secret,), SecretError> {
+        let keyring = self.keyring.lock().unwrap();
+        if keyring.locked {
+            return Err(SecretError::IsLocked("the item is locked".to_string()));
+        }
+        Ok((Secret {
+            session,
+            parameters: Vec::new(),
+            value: keyring.items[self.index].secret.clone(),
+            content_type: "text/plain".to_string(),
+        },))
+    }
+
+    fn set_secret(&self, secret: Secret) -> Result<(), SecretError> {
+        let mut keyring = self.keyring.lock().unwrap();
+        if keyring.locked {
+            return Err(SecretError::IsLocked("the item is locked".to_string()));
+        }
+        keyring.items[self.index].secret = secret.value;
+        Ok(())
+    }
+
+    #[zbus(property)]
+    fn attributes(&self) -> HashMap<String, String> {
+        self.keyring.lock().unwrap().items[self.index]
+            .attributes
+            .clone()
+    }
+
+    #[zbus(property)]
+    fn locked(&self) -> bool {
+        self.keyring.lock().unwrap().locked
+    }
+}
+
+/// Serve `keyring` as `org.freedesktop.secrets` on `bus` until dropped
+async fn serve_keyring(bus: &PrivateBus, keyring: &Shared) -> Connection {
+    let mut builder = zbus::connection::Builder::address(bus.address.as_str())
+        .unwrap()
+        .name("org.freedesktop.secrets")
+        .unwrap()
+        .serve_at(
+            SERVICE_PATH,
+            Service {
+                keyring: keyring.clone(),
+            },
+        )
+        .unwrap()
+        .serve_at(SESSION_PATH, Session)
+        .unwrap();
+    for index in 0..keyring.lock().unwrap().items.len() {
+        let item = Item {
+            keyring: keyring.clone(),
+            index,
+        };
+        builder = builder.serve_at(item_path(index), item).unwrap();
+    }
+    builder.build().await.unwrap()
+}
+
+/// A keyring with an expired token stored the way `secret-tool` would,
+/// plus an unrelated item
+fn keyring_with_expired_token(locked: bool) -> Shared {
+    let expired = chrono::Utc::now().timestamp_millis() - HOUR_MS;
+    let attributes = |service: &str| {
+        HashMap::from([
+            ("service".to_string(), service.to_string()),
+            ("account".to_string(), "someone".to_string()),
+            (
+                "xdg:schema".to_string(),
+                "org.freedesktop.Secret.Generic".to_string(),
+            ),
+        ])
+    };
+    Arc::new(Mutex::new(Keyring {
+        items: vec![
+            StoredItem {
+                attributes: attributes("other-app"),
+                secret: b"unrelated".to_vec(),
+            },
+            StoredItem {
+                attributes: attributes("work-creds"),
+                secret: format!(
+                    r#"{{"claudeAiOauth":{{"accessToken":"old","refreshToken":"old-refresh","expiresAt":{}}},"other":1}}"#,
+                    expired
+                )
+                .into_bytes(),
+            },
+        ],
+        locked,
+        ..Keyring::default()
+    }))
+}
+
+/// Read credentials from the `work-creds` item, over `bus`
+fn use_secret_service(bus: &PrivateBus) {
+    std::env::set_var("DBUS_SESSION_BUS_ADDRESS", &bus.address);
+    let mut settings = settings_store::load_settings().unwrap();
+    settings.credential_providers = vec![CredentialSource::SecretService {
+        service: "work-creds".to_string(),
+    }];
+    settings_store::save_settings(&settings).unwrap();
+}
+
+#[tokio::test]
+async fn unlocks_the_keyring_and_stores_refreshed_tokens() {
+    let Some(bus) = PrivateBus::start() else {
+        eprintln!("skipped: dbus-daemon is not installed");
+        return;
+    };
+    let env = TestEnv::new().await;
+    let token = r#"{"access_token":"new-token","refresh_token":"new-refresh","expires_in":3600}"#;
+    MockServer::serve(&env, vec![respond(200, token)]).await;
+    let keyring = keyring_with_expired_token(true);
+    let _service = serve_keyring(&bus, &keyring).await;
+    use_secret_service(&bus);
+
+    let status = check_credentials(None).await.unwrap();
+
+    assert!(status.authenticated);
+    assert_eq!(status.source.as_deref(), Some("secret_service"));
+    let keyring = keyring.lock().unwrap();
+    assert_eq!(keyring.prompts_shown, 1);
+    assert_eq!(keyring.items.len(), 2);
+    assert_eq!(keyring.items[0].secret, b"unrelated");
+    let stored = &keyring.items[1];
+    assert_eq!(stored.attributes["account"], "someone");
+    let stored: serde_json::Value = serde_json::from_slice(&stored.secret).unwrap();
+    assert_eq!(stored["claudeAiOauth"]["accessToken"], "new-token");
+    assert_eq!(stored["claudeAiOauth"]["refreshToken"], "new-refresh");
+    assert_eq!(stored["other"], 1);
+}
+
+#[tokio::test]
+async fn reports_a_dismissed_unlock_prompt() {
+    let Some(bus) = PrivateBus::start() else {
+        eprintln!("skipped: dbus-daemon is not installed");
+        return;
+    };
+    let _env = TestEnv::new().await;
+    let keyring = keyring_with_expired_token(true);
+    keyring.lock().unwrap().dismiss_prompts = true;
+    let _service = serve_keyring(&bus, &keyring).await;
+    use_secret_service(&bus);
+
+    let status = check_credentials(None).await.unwrap();
+
+    assert!(!status.authenticated);
+    assert_eq!(status.error_reason.as_deref(), Some("secret_service_error"));
+    assert!(keyring.lock().unwrap().locked);
+}
+
+#[tokio::test]
+async fn treats_a_bus_without_a_keyring_as_no_credentials() {
+    let Some(bus) = PrivateBus::start() else {
+        eprintln!("skipped: dbus-daemon is not installed");
+        return;
+    };
+    let _env = TestEnv::new().await;
+    use_secret_service(&bus);
+
+    let status = check_credentials(None).await.unwrap();
+
+    assert!(!status.authenticated);
+    assert_eq!(status.error_reason.as_deref(), Some("not_found"));
+    assert_eq!(status.skipped_providers.len(), 1);
+}
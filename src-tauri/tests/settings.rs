@@ -0,0 +1,186 @@
+//! `settings.json` on disk: backups, recovery from a corrupt file and
+//! upgrading files written by older versions
+
+mod common;
+
+use std::path::PathBuf;
+
+use claude_code_usage_monitor_lib::services::diagnostics;
+use claude_code_usage_monitor_lib::services::settings_migrations::CURRENT_SCHEMA_VERSION;
+use claude_code_usage_monitor_lib::services::settings_store::{self, SettingsError};
+use common::TestEnv;
+
+#[tokio::test]
+async fn quarantines_corrupt_settings_and_restores_backup() {
+    let _env = TestEnv::new().await;
+    let path = settings_store::get_settings_path().unwrap();
+
+    let mut settings = settings_store::load_settings().unwrap();
+    settings.first_launch = false;
+    settings_store::save_settings(&settings).unwrap();
+    settings.api_base_url = Some("http://localhost:1".to_string());
+    settings_store::save_settings(&settings).unwrap();
+
+    let backup: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(path.with_extension("json.bak.1")).unwrap())
+            .unwrap();
+    assert_eq!(backup["first_launch"], false);
+    assert!(backup.get("api_base_url").is_none());
+    assert!(!path.with_extension("json.tmp").exists());
+
+    std::fs::write(&path, "{\"shortcut\": {").unwrap();
+    assert!(matches!(
+        settings_store::load_settings(),
+        Err(SettingsError::Corrupt { .. })
+    ));
+    // Writers refuse rather than replace the file with defaults
+    assert!(claude_code_usage_monitor_lib::commands::complete_first_launch().is_err());
+
+    let recovery = settings_store::recover_corrupt_settings().unwrap();
+    assert!(recovery.restored_from_backup);
+    assert_eq!(
+        std::fs::read_to_string(&recovery.quarantined_path).unwrap(),
+        "{\"shortcut\": {"
+    );
+    assert!(!settings_store::load_settings().unwrap().first_launch);
+    assert!(settings_store::take_settings_recovery().is_some());
+    assert!(settings_store::take_settings_recovery().is_none());
+}
+
+#[tokio::test]
+async fn keeps_three_generations_of_backups() {
+    let _env = TestEnv::new().await;
+    let path = settings_store::get_settings_path().unwrap();
+    let base_url = |path: PathBuf| {
+        let stored: serde_json::Value =
+            serde_json::from_str(&std::fs::read_to_string(path).unwrap()).unwrap();
+        stored["api_base_url"].as_str().unwrap().to_string()
+    };
+
+    let mut settings = settings_store::load_settings().unwrap();
+    for port in 1..=5 {
+        settings.api_base_url = Some(format!("http://localhost:{}", port));
+        settings_store::save_settings(&settings).unwrap();
+    }
+
+    assert_eq!(base_url(path.clone()), "http://localhost:5");
+    assert_eq!(
+        base_url(path.with_extension("json.bak.1")),
+        "http://localhost:4"
+    );
+    assert_eq!(
+        base_url(path.with_extension("json.bak.2")),
+        "http://localhost:3"
+    );
+    assert_eq!(
+        base_url(path.with_extension("json.bak.3")),
+        "http://localhost:2"
+    );
+    assert!(!path.with_extension("json.bak.4").exists());
+
+    // The newest usable generation is restored
+    std::fs::write(&path, "not json").unwrap();
+    std::fs::write(path.with_extension("json.bak.1"), "not json either").unwrap();
+    let recovery = settings_store::recover_corrupt_settings().unwrap();
+    assert!(recovery.restored_from_backup);
+    assert_eq!(base_url(path), "http://localhost:3");
+    settings_store::take_settings_recovery();
+}
+
+/// Every key in `expected` has the same value in `actual`, recursively
+fn assert_contains(expected: &serde_json::Value, actual: &serde_json::Value, context: &str) {
+    match (expected, actual) {
+        (serde_json::Value::Object(expected), serde_json::Value::Object(actual)) => {
+            for (key, value) in expected {
+                let context = format!("{}.{}", context, key);
+                let actual = actual
+                    .get(key)
+                    .unwrap_or_else(|| panic!("{} is missing", context));
+                assert_contains(value, actual, &context);
+            }
+        }
+        _ => assert_eq!(expected, actual, "{}", context),
+    }
+}
+
+#[tokio::test]
+async fn upgrades_settings_fixtures_to_the_current_schema() {
+    let fixtures = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/settings");
+
+    let mut inputs: Vec<PathBuf> = std::fs::read_dir(&fixtures)
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| !path.to_string_lossy().ends_with(".expected.json"))
+        .collect();
+    inputs.sort();
+    assert!(!inputs.is_empty());
+
+    for input in inputs {
+        let name = input.file_stem().unwrap().to_string_lossy().to_string();
+        let expected: serde_json::Value = serde_json::from_str(
+            &std::fs::read_to_string(fixtures.join(format!("{}.expected.json", name))).unwrap(),
+        )
+        .unwrap();
+
+        let _env = TestEnv::new().await;
+        let path = settings_store::get_settings_path().unwrap();
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        let original = std::fs::read_to_string(&input).unwrap();
+        std::fs::write(&path, &original).unwrap();
+        let stored: serde_json::Value = serde_json::from_str(&original).unwrap();
+        let stored_version = stored["schema_version"].as_u64().unwrap_or(0);
+
+        let loaded = serde_json::to_value(settings_store::load_settings().unwrap()).unwrap();
+        assert_contains(&expected, &loaded, &name);
+
+        // The upgrade writes the same settings back, keeping the old file
+        settings_store::upgrade_settings_file().unwrap();
+        let rewritten: serde_json::Value =
+            serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+        assert_contains(&expected, &rewritten, &name);
+        let kept = path.with_extension(format!("json.v{}.bak", stored_version));
+        let set_aside = expected.get("unrecognized").is_some();
+        if stored_version < CURRENT_SCHEMA_VERSION as u64 || set_aside {
+            assert_eq!(
+                std::fs::read_to_string(&kept).unwrap(),
+                original,
+                "{}",
+                name
+            );
+        } else {
+            assert!(!kept.exists(), "{}", name);
+            assert_eq!(
+                std::fs::read_to_string(&path).unwrap(),
+                original,
+                "{}",
+                name
+            );
+        }
+        if set_aside {
+            let log =
+                std::fs::read_to_string(diagnostics::get_diagnostics_path().unwrap()).unwrap();
+            assert!(log.contains("under \"unrecognized\""), "{}", name);
+        }
+    }
+}
+
+#[tokio::test]
+async fn saving_keeps_keys_this_build_does_not_know() {
+    let _env = TestEnv::new().await;
+    let path = settings_store::get_settings_path().unwrap();
+    std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+    std::fs::write(
+        &path,
+        r#"{"schema_version": 1, "shortcut": {"modifier": "alt", "key": "r"}, "first_launch": true, "added_later": {"on": true}}"#,
+    )
+    .unwrap();
+
+    let mut settings = settings_store::load_settings().unwrap();
+    settings.first_launch = false;
+    settings_store::save_settings(&settings).unwrap();
+
+    let saved: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+    assert_eq!(saved["first_launch"], false);
+    assert_eq!(saved["added_later"], serde_json::json!({"on": true}));
+}
@@ -0,0 +1,163 @@
+//! Fetching usage against a local stand-in for the API: status mapping,
+//! retries, the refresh after a 401 and tolerant decoding
+
+mod common;
+
+use claude_code_usage_monitor_lib::services::usage_scheduler::{load_usage, UsageError};
+use claude_code_usage_monitor_lib::services::ApiError;
+use common::{active_profile, respond, CannedResponse, MockServer, TestEnv, HOUR_MS, USAGE_BODY};
+
+#[tokio::test]
+async fn fetches_usage_on_200() {
+    let (env, _) = TestEnv::with_credentials(HOUR_MS, None).await;
+    let server = MockServer::serve(&env, vec![respond(200, USAGE_BODY)]).await;
+
+    let usage = load_usage(&active_profile()).await.unwrap();
+
+    assert_eq!(usage.five_hour.unwrap().utilization, 12.0);
+    assert_eq!(usage.seven_day.unwrap().utilization, 34.5);
+    assert!(usage.seven_day_opus.is_none());
+    assert_eq!(server.requests(), vec!["/api/oauth/usage"]);
+}
+
+#[tokio::test]
+async fn maps_401_to_unauthorized_without_refresh_token() {
+    let (env, _) = TestEnv::with_credentials(HOUR_MS, None).await;
+    MockServer::serve(&env, vec![respond(401, r#"{"error":"invalid"}"#)]).await;
+
+    let error = load_usage(&active_profile()).await.unwrap_err();
+
+    assert!(matches!(error, UsageError::Api(ApiError::Unauthorized)));
+}
+
+#[tokio::test]
+async fn refreshes_and_retries_after_401() {
+    let (env, path) = TestEnv::with_credentials(HOUR_MS, Some("old-refresh")).await;
+    let token = r#"{"access_token":"new-token","refresh_token":"new-refresh","expires_in":3600}"#;
+    let server = MockServer::serve(
+        &env,
+        vec![
+            respond(401, "{}"),
+            respond(200, token),
+            respond(200, USAGE_BODY),
+        ],
+    )
+    .await;
+
+    let usage = load_usage(&active_profile()).await.unwrap();
+
+    assert_eq!(usage.five_hour.unwrap().utilization, 12.0);
+    assert_eq!(
+        server.requests(),
+        vec!["/api/oauth/usage", "/v1/oauth/token", "/api/oauth/usage"]
+    );
+    let saved: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(path).unwrap()).unwrap();
+    assert_eq!(saved["claudeAiOauth"]["accessToken"], "new-token");
+    assert_eq!(saved["claudeAiOauth"]["refreshToken"], "new-refresh");
+}
+
+#[tokio::test]
+async fn maps_429_to_rate_limited_with_retry_after() {
+    let (env, _) = TestEnv::with_credentials(HOUR_MS, None).await;
+    let server = MockServer::serve(
+        &env,
+        vec![CannedResponse {
+            status: 429,
+            headers: vec![("Retry-After", "40".to_string())],
+            body: "{}".to_string(),
+        }],
+    )
+    .await;
+
+    let error = load_usage(&active_profile()).await.unwrap_err();
+
+    assert!(matches!(
+        error,
+        UsageError::Api(ApiError::RateLimited {
+            retry_after: Some(40)
+        })
+    ));
+    assert_eq!(error.retry_after(), Some(40));
+    assert_eq!(server.requests().len(), 1);
+}
+
+#[tokio::test]
+async fn retries_500_before_succeeding() {
+    let (env, _) = TestEnv::with_credentials(HOUR_MS, None).await;
+    let server = MockServer::serve(
+        &env,
+        vec![
+            respond(500, "boom"),
+            respond(503, "busy"),
+            respond(200, USAGE_BODY),
+        ],
+    )
+    .await;
+
+    let usage = load_usage(&active_profile()).await.unwrap();
+
+    assert_eq!(usage.seven_day.unwrap().utilization, 34.5);
+    assert_eq!(server.requests().len(), 3);
+}
+
+#[tokio::test]
+async fn gives_up_on_persistent_500() {
+    let (env, _) = TestEnv::with_credentials(HOUR_MS, None).await;
+    let server = MockServer::serve(&env, (0..4).map(|_| respond(500, "boom")).collect()).await;
+
+    let error = load_usage(&active_profile()).await.unwrap_err();
+
+    assert!(matches!(
+        error,
+        UsageError::Api(ApiError::Server { status: 500, .. })
+    ));
+    assert_eq!(server.requests().len(), 4);
+}
+
+#[tokio::test]
+async fn keeps_unknown_buckets_and_tolerates_missing_fields() {
+    let (env, _) = TestEnv::with_credentials(HOUR_MS, None).await;
+    let body = r#"{
+        "seven_day": {"utilization": 50.0, "resets_at": null},
+        "seven_day_oauth_apps": {"utilization": 7.0, "resets_at": null},
+        "extra_usage": {"is_enabled": false}
+    }"#;
+    MockServer::serve(&env, vec![respond(200, body)]).await;
+
+    let usage = load_usage(&active_profile()).await.unwrap();
+
+    assert!(usage.five_hour.is_none());
+    let keys: Vec<_> = usage.buckets.iter().map(|b| b.key.as_str()).collect();
+    assert_eq!(keys, vec!["seven_day", "seven_day_oauth_apps"]);
+    assert_eq!(usage.buckets[1].label, "Seven Day Oauth Apps");
+    assert!(!usage.buckets[1].known);
+    assert_eq!(usage.schema_drift.len(), 3);
+}
+
+#[tokio::test]
+async fn maps_malformed_body_to_decode_error() {
+    let (env, _) = TestEnv::with_credentials(HOUR_MS, None).await;
+    MockServer::serve(&env, vec![respond(200, "{not json")]).await;
+
+    let error = load_usage(&active_profile()).await.unwrap_err();
+
+    assert!(matches!(error, UsageError::Api(ApiError::Decode(_))));
+}
+
+#[tokio::test]
+async fn scrubs_secrets_from_error_bodies() {
+    let (env, _) = TestEnv::with_credentials(HOUR_MS, None).await;
+    let body = r#"{"error":{"message":"bad token sk-ant-REDACTED","access_token":"leaked"}}"#;
+    MockServer::serve(&env, vec![respond(400, body)]).await;
+
+    let error = load_usage(&active_profile()).await.unwrap_err();
+    let message = error.to_string();
+
+    assert!(matches!(
+        error,
+        UsageError::Api(ApiError::Response { status: 400, .. })
+    ));
+    assert!(!message.contains("sk-ant-oat01"));
+    assert!(!message.contains("leaked"));
+}
@@ -3,10 +3,13 @@ import { listen } from "@tauri-apps/api/event";
 import { getCurrentWindow, LogicalSize } from "@tauri-apps/api/window";
 import { useAuth } from "./hooks/useAuth";
 import { useSettings } from "./hooks/useSettings";
+import { useProfiles } from "./hooks/useProfiles";
 import { Overlay } from "./components/Overlay/Overlay";
 import { Dashboard } from "./components/Dashboard/Dashboard";
 import { OnboardingWizard } from "./components/OnboardingWizard";
 import { LoginRequired } from "./components/LoginRequired";
+import { SettingsRecoveryNotice } from "./components/SettingsRecoveryNotice";
+import { MissingDirsNotice } from "./components/MissingDirsNotice";
 
 function App() {
   const [isVisible, setIsVisible] = useState(true);
@@ -20,6 +23,9 @@ function App() {
     completeFirstLaunch,
     centerWindow,
   } = useSettings();
+  const { profiles } = useProfiles();
+  // The profile switcher takes an extra row
+  const hasProfileSwitcher = profiles.length > 1;
 
   useEffect(() => {
     const unlisten = listen<boolean>("overlay-visibility", (event) => {
@@ -45,9 +51,9 @@ function App() {
         // Set size based on layout type
         const layoutType = settings?.layout?.layout_type ?? "simple";
         if (layoutType === "detailed") {
-          await window.setSize(new LogicalSize(380, 360));
+          await window.setSize(new LogicalSize(380, hasProfileSwitcher ? 390 : 360));
         } else {
-          await window.setSize(new LogicalSize(140, 75));
+          await window.setSize(new LogicalSize(140, hasProfileSwitcher ? 90 : 75));
         }
       }
     };
@@ -55,7 +61,7 @@ function App() {
     if (platformInfo !== null && settings !== null) {
       adjustWindowSize();
     }
-  }, [platformInfo, settings, centerWindow, authLoading, authStatus]);
+  }, [platformInfo, settings, centerWindow, authLoading, authStatus, hasProfileSwitcher]);
 
   const isLoading = authLoading || settingsLoading;
 
@@ -69,6 +75,8 @@ function App() {
   if (platformInfo?.is_first_launch) {
     return (
       <div className="bg-slate-900 h-full pt-8">
+        <MissingDirsNotice />
+        <SettingsRecoveryNotice />
         <OnboardingWizard
           platformName={platformInfo.name}
           onComplete={async (modifier, key, layoutType) => {
@@ -95,6 +103,9 @@ function App() {
         <LoginRequired
           errorReason={authStatus?.error_reason ?? null}
           credentialsPath={authStatus?.credentials_path ?? ""}
+          skippedProviders={authStatus?.skipped_providers}
+          permissionWarning={authStatus?.permission_warning}
+          missingScopes={authStatus?.missing_scopes}
         />
       );
     }
@@ -108,6 +119,8 @@ function App() {
 
   return (
     <div className={`${bgClass} rounded-md overflow-hidden`}>
+      <MissingDirsNotice />
+      <SettingsRecoveryNotice />
       {renderContent()}
     </div>
   );
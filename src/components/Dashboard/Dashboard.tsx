@@ -1,18 +1,98 @@
-import { useState, useEffect } from "react";
+import { useState } from "react";
 import { RefreshCw } from "lucide-react";
 import { useUsageData } from "../../hooks/useUsageData";
+import { useProfiles } from "../../hooks/useProfiles";
+import { useAuth } from "../../hooks/useAuth";
+import { useExpiryWarning } from "../../hooks/useExpiryWarning";
+import { PermissionWarning } from "../PermissionWarning";
 import { UsageCard } from "../UsageCard/UsageCard";
 import { formatLastUpdated } from "../../utils/formatters";
 
 export function Dashboard() {
-  const { data, isLoading, error, refetch, isFetching } = useUsageData();
-  const [lastUpdated, setLastUpdated] = useState<Date>(new Date());
+  const { profiles, activeProfile, switchProfile } = useProfiles();
+  const { data: authStatus } = useAuth();
+  const expiryWarning = useExpiryWarning(authStatus?.profile_id, authStatus?.expires_at ?? null);
+  const [sideBySide, setSideBySide] = useState(false);
 
-  useEffect(() => {
-    if (data && !isFetching) {
-      setLastUpdated(new Date());
-    }
-  }, [data, isFetching]);
+  const skew = authStatus?.clock_skew_seconds;
+  const banners = (
+    <>
+      {authStatus?.permission_warning && (
+        <div className="px-3 pt-3">
+          <PermissionWarning warning={authStatus.permission_warning} />
+        </div>
+      )}
+      {expiryWarning && (
+        <div className="mx-3 mt-3 bg-amber-900/20 border border-amber-700/50 rounded-lg p-2 text-[10px] text-amber-300">
+          Login expires in {Math.max(expiryWarning.minutes_left, 1)} min. Run{" "}
+          <code className="text-green-400">claude</code> to log in again.
+        </div>
+      )}
+      {skew != null && (
+        <div className="mx-3 mt-3 text-[10px] text-amber-400">
+          System clock is {Math.abs(skew)}s {skew > 0 ? "behind" : "ahead of"} the server; token
+          times may be off.
+        </div>
+      )}
+    </>
+  );
+
+  if (profiles.length <= 1) {
+    return (
+      <div>
+        {banners}
+        <ProfileUsage profileId={activeProfile} />
+      </div>
+    );
+  }
+
+  const tabClass = (selected: boolean) =>
+    `px-2 py-0.5 rounded transition-colors ${
+      selected ? "bg-slate-700 text-slate-100" : "bg-slate-800 text-slate-400 hover:bg-slate-700"
+    }`;
+
+  return (
+    <div>
+      {banners}
+      {/* Profile switcher */}
+      <div className="flex gap-1 px-3 pt-3 text-[10px]">
+        {profiles.map((profile) => (
+          <button
+            key={profile.id}
+            onClick={() => {
+              setSideBySide(false);
+              switchProfile(profile.id);
+            }}
+            className={tabClass(!sideBySide && profile.id === activeProfile)}
+          >
+            {profile.name}
+          </button>
+        ))}
+        <button onClick={() => setSideBySide(true)} className={tabClass(sideBySide)}>
+          All
+        </button>
+      </div>
+
+      {sideBySide ? (
+        <div className="grid grid-cols-2">
+          {profiles.map((profile) => (
+            <ProfileUsage key={profile.id} profileId={profile.id} title={profile.name} />
+          ))}
+        </div>
+      ) : (
+        <ProfileUsage profileId={activeProfile} />
+      )}
+    </div>
+  );
+}
+
+interface ProfileUsageProps {
+  profileId: string | undefined;
+  title?: string;
+}
+
+function ProfileUsage({ profileId, title }: ProfileUsageProps) {
+  const { data, isLoading, error, refetch, isFetching } = useUsageData(profileId);
 
   if (isLoading) {
     return (
@@ -30,11 +110,13 @@ export function Dashboard() {
     );
   }
 
-  if (error) {
+  if (error && !data) {
     return (
       <div className="p-3">
         <div className="bg-red-900/20 border border-red-800/50 rounded-lg p-3 text-center">
-          <p className="text-red-400 text-xs mb-2">Failed to load</p>
+          <p className="text-red-400 text-xs mb-2">
+            {error.message || "Failed to load"}
+          </p>
           <button
             onClick={() => refetch()}
             className="px-3 py-1 bg-red-600 hover:bg-red-700 rounded text-xs font-medium transition-colors"
@@ -50,20 +132,20 @@ export function Dashboard() {
 
   return (
     <div className="p-3 space-y-2">
-      <UsageCard title="5-Hour" metric={data.five_hour} />
-      <UsageCard title="7-Day" metric={data.seven_day} />
-      {data.seven_day_sonnet && (
-        <UsageCard title="Sonnet" metric={data.seven_day_sonnet} />
-      )}
-      {data.seven_day_opus && (
-        <UsageCard title="Opus" metric={data.seven_day_opus} />
-      )}
+      {title && <h2 className="text-[11px] font-semibold text-slate-300">{title}</h2>}
+
+      <div className={data.stale_since ? "space-y-2 opacity-50 grayscale" : "space-y-2"}>
+        {data.buckets.map((bucket) => (
+          <UsageCard key={bucket.key} title={bucket.label} metric={bucket.metric} />
+        ))}
+      </div>
 
       {/* Footer */}
       <div className="flex items-center justify-between pt-1 text-[10px] text-slate-500">
         <span className="flex items-center gap-1">
           {isFetching && <RefreshCw className="w-2.5 h-2.5 animate-spin" />}
-          {formatLastUpdated(lastUpdated)}
+          {data.stale_since && <span className="text-amber-500">Offline ·</span>}
+          {data.fetched_at ? formatLastUpdated(new Date(data.fetched_at)) : "--"}
         </span>
         <button
           onClick={() => refetch()}
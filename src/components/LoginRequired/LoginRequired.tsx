@@ -1,12 +1,41 @@
+import { useState } from "react";
+import { useQueryClient } from "@tanstack/react-query";
+import { startOauthLogin } from "../../services/tauri-commands";
+import type { AuthStatus, FilePermissionWarning, ProviderAttempt } from "../../services/types";
+import { PermissionWarning } from "../PermissionWarning";
+
 interface LoginRequiredProps {
   errorReason: string | null;
   credentialsPath: string;
+  skippedProviders?: ProviderAttempt[];
+  permissionWarning?: FilePermissionWarning | null;
+  missingScopes?: string[];
 }
 
 export function LoginRequired({
   errorReason,
   credentialsPath,
+  skippedProviders = [],
+  permissionWarning,
+  missingScopes = [],
 }: LoginRequiredProps) {
+  const queryClient = useQueryClient();
+  const [loggingIn, setLoggingIn] = useState(false);
+  const [loginError, setLoginError] = useState<string | null>(null);
+
+  const handleBrowserLogin = async () => {
+    setLoggingIn(true);
+    setLoginError(null);
+    try {
+      const status = await startOauthLogin();
+      queryClient.setQueryData<AuthStatus>(["auth", "active"], status);
+    } catch (e) {
+      setLoginError(String(e));
+    } finally {
+      setLoggingIn(false);
+    }
+  };
+
   const getErrorMessage = () => {
     switch (errorReason) {
       case "not_found":
@@ -15,10 +44,20 @@ export function LoginRequired({
         return "No OAuth credentials";
       case "token_expired":
         return "Token expired";
+      case "refresh_failed":
+        return "Token refresh failed";
       case "read_error":
         return "Cannot read credentials";
       case "parse_error":
         return "Invalid credentials format";
+      case "process_error":
+        return "Credential process failed";
+      case "secret_service_error":
+        return "Secret Service unavailable";
+      case "insufficient_scope":
+        return "Missing permissions";
+      case "no_home_dir":
+        return "Home directory not found";
       default:
         return "Login required";
     }
@@ -38,6 +77,7 @@ export function LoginRequired({
           </>
         );
       case "token_expired":
+      case "refresh_failed":
         return (
           <>
             <p className="mb-2">Your session has expired.</p>
@@ -47,6 +87,19 @@ export function LoginRequired({
             </code>
           </>
         );
+      case "insufficient_scope":
+        return (
+          <>
+            <p className="mb-2">
+              Token lacks {missingScopes.join(", ") || "a required scope"}. Tokens from{" "}
+              <code className="text-yellow-400">claude setup-token</code> cannot read usage.
+            </p>
+            <p className="mb-1">Run in terminal, then /logout and /login:</p>
+            <code className="block bg-black/50 px-2 py-1 rounded text-green-400 mb-2">
+              claude
+            </code>
+          </>
+        );
       case "read_error":
         return (
           <>
@@ -57,6 +110,15 @@ export function LoginRequired({
             <p className="text-slate-500">Check file permissions.</p>
           </>
         );
+      case "no_home_dir":
+        return (
+          <>
+            <p className="mb-2">Could not determine your home directory.</p>
+            <p className="text-slate-500">
+              Set CLAUDE_CONFIG_DIR or a credentials path in settings.
+            </p>
+          </>
+        );
       default:
         return (
           <>
@@ -73,6 +135,28 @@ export function LoginRequired({
     <div className="p-3 text-[10px] text-slate-300 font-mono">
       <div className="text-red-400 font-semibold mb-2">{getErrorMessage()}</div>
       <div className="text-slate-400">{getInstructions()}</div>
+      <button
+        onClick={handleBrowserLogin}
+        disabled={loggingIn}
+        className="mt-2 px-2 py-0.5 rounded bg-blue-600 hover:bg-blue-500 disabled:bg-slate-700 text-white transition-colors"
+      >
+        {loggingIn ? "Waiting for browser..." : "Log in with browser"}
+      </button>
+      {loginError && <p className="mt-1 text-red-400 break-all">{loginError}</p>}
+      {skippedProviders.length > 0 && (
+        <ul className="mt-2 space-y-0.5 text-[8px] text-slate-500">
+          {skippedProviders.map((attempt) => (
+            <li key={`${attempt.provider}:${attempt.location}`} className="break-all">
+              {attempt.provider}: {attempt.reason}
+            </li>
+          ))}
+        </ul>
+      )}
+      {permissionWarning && (
+        <div className="mt-2">
+          <PermissionWarning warning={permissionWarning} />
+        </div>
+      )}
     </div>
   );
 }
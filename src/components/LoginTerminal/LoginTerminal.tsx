@@ -0,0 +1,133 @@
+import { useEffect, useRef, useState } from "react";
+import { listen } from "@tauri-apps/api/event";
+import {
+  startLoginTerminal,
+  writeLoginTerminal,
+  resizeLoginTerminal,
+  stopLoginTerminal,
+} from "../../services/tauri-commands";
+import type { LoginTerminalExit, LoginTerminalOutput } from "../../services/types";
+import { TerminalScreen, keyToInput } from "../../utils/terminalScreen";
+
+const DEFAULT_COLS = 80;
+const DEFAULT_ROWS = 24;
+
+interface LoginTerminalProps {
+  profileId?: string;
+  onExit: (exit: LoginTerminalExit) => void;
+  onError: (message: string) => void;
+}
+
+/** `claude` running in a pseudo-terminal owned by the backend */
+export function LoginTerminal({ profileId, onExit, onError }: LoginTerminalProps) {
+  const containerRef = useRef<HTMLDivElement>(null);
+  const probeRef = useRef<HTMLSpanElement>(null);
+  const screenRef = useRef(new TerminalScreen(DEFAULT_COLS, DEFAULT_ROWS));
+  const sessionRef = useRef<number | null>(null);
+  const sizeRef = useRef({ cols: DEFAULT_COLS, rows: DEFAULT_ROWS });
+  const onExitRef = useRef(onExit);
+  onExitRef.current = onExit;
+  const onErrorRef = useRef(onError);
+  onErrorRef.current = onError;
+  const [lines, setLines] = useState<string[]>([]);
+
+  // Output can arrive before `start` resolves with the session id, so keep it
+  // until the id is known
+  useEffect(() => {
+    let early: LoginTerminalOutput[] = [];
+    const render = (output: LoginTerminalOutput) => {
+      screenRef.current.write(output.data);
+      setLines(screenRef.current.lines());
+    };
+
+    const unlistenOutput = listen<LoginTerminalOutput>("login-terminal-output", (event) => {
+      if (sessionRef.current === null) {
+        early.push(event.payload);
+      } else if (event.payload.session_id === sessionRef.current) {
+        render(event.payload);
+      }
+    });
+    const unlistenExit = listen<LoginTerminalExit>("login-terminal-exit", (event) => {
+      if (event.payload.session_id === sessionRef.current) {
+        onExitRef.current(event.payload);
+      }
+    });
+
+    startLoginTerminal(DEFAULT_COLS, DEFAULT_ROWS, profileId)
+      .then((sessionId) => {
+        sessionRef.current = sessionId;
+        // The panel may have been measured while the child was starting
+        const { cols, rows } = sizeRef.current;
+        if (cols !== DEFAULT_COLS || rows !== DEFAULT_ROWS) {
+          resizeLoginTerminal(cols, rows).catch(() => {});
+        }
+        early.filter((output) => output.session_id === sessionId).forEach(render);
+        early = [];
+        containerRef.current?.focus();
+      })
+      .catch((e) => onErrorRef.current(String(e)));
+
+    return () => {
+      unlistenOutput.then((fn) => fn());
+      unlistenExit.then((fn) => fn());
+      stopLoginTerminal().catch(() => {
+        // Already exited
+      });
+    };
+  }, [profileId]);
+
+  // Match the pty size to the space the panel actually has
+  useEffect(() => {
+    const container = containerRef.current;
+    const probe = probeRef.current;
+    if (!container || !probe) return;
+
+    const observer = new ResizeObserver(() => {
+      const charWidth = probe.getBoundingClientRect().width;
+      const lineHeight = probe.getBoundingClientRect().height;
+      if (!charWidth || !lineHeight) return;
+
+      const cols = Math.max(20, Math.floor(container.clientWidth / charWidth));
+      const rows = Math.max(5, Math.floor(container.clientHeight / lineHeight));
+      sizeRef.current = { cols, rows };
+      screenRef.current.resize(cols, rows);
+      if (sessionRef.current !== null) {
+        resizeLoginTerminal(cols, rows).catch(() => {});
+      }
+    });
+    observer.observe(container);
+    return () => observer.disconnect();
+  }, []);
+
+  useEffect(() => {
+    const container = containerRef.current;
+    if (container) container.scrollTop = container.scrollHeight;
+  }, [lines]);
+
+  const handleKeyDown = (event: React.KeyboardEvent) => {
+    const input = keyToInput(event);
+    if (input === null) return;
+    event.preventDefault();
+    writeLoginTerminal(input).catch(() => {});
+  };
+
+  const handlePaste = (event: React.ClipboardEvent) => {
+    event.preventDefault();
+    writeLoginTerminal(event.clipboardData.getData("text")).catch(() => {});
+  };
+
+  return (
+    <div
+      ref={containerRef}
+      tabIndex={0}
+      onKeyDown={handleKeyDown}
+      onPaste={handlePaste}
+      className="relative h-full overflow-auto bg-black/80 rounded-lg p-2 font-mono text-[10px] leading-tight text-slate-200 outline-none focus:ring-1 focus:ring-blue-500/50"
+    >
+      <span ref={probeRef} className="invisible absolute">
+        M
+      </span>
+      <pre className="whitespace-pre">{lines.join("\n")}</pre>
+    </div>
+  );
+}
@@ -0,0 +1 @@
+export { LoginTerminal } from "./LoginTerminal";
@@ -0,0 +1,34 @@
+import { useEffect, useState } from "react";
+import { AlertTriangle, X } from "lucide-react";
+import { takeMissingDirs } from "../../services/tauri-commands";
+
+/** Tells the user once that there is nowhere to save settings, found at startup */
+export function MissingDirsNotice() {
+  const [error, setError] = useState<string | null>(null);
+
+  useEffect(() => {
+    takeMissingDirs()
+      .then(setError)
+      .catch(() => setError(null));
+  }, []);
+
+  if (!error) return null;
+
+  return (
+    <div className="m-1 bg-red-900/20 border border-red-700/50 rounded-lg p-2 text-[10px] text-red-300">
+      <div className="flex items-start gap-1.5">
+        <AlertTriangle className="w-3 h-3 flex-shrink-0 mt-px" />
+        <p className="flex-1 break-all">
+          {error}. Changes won't be kept after the app closes.
+        </p>
+        <button
+          onClick={() => setError(null)}
+          className="text-red-400 hover:text-red-200 transition-colors"
+          aria-label="Dismiss"
+        >
+          <X className="w-3 h-3" />
+        </button>
+      </div>
+    </div>
+  );
+}
@@ -0,0 +1 @@
+export { MissingDirsNotice } from "./MissingDirsNotice";
@@ -1,9 +1,25 @@
 import { useState, useEffect, useCallback, useRef } from "react";
-import { CheckCircle, AlertCircle, RefreshCw, User, ExternalLink } from "lucide-react";
-import { AuthStatus } from "../../services/types";
-import { launchClaudeCli, closeClaudeTerminal } from "../../services/tauri-commands";
+import {
+  CheckCircle,
+  AlertCircle,
+  RefreshCw,
+  User,
+  ExternalLink,
+  Globe,
+  Terminal,
+} from "lucide-react";
+import { AuthStatus, ClaudeCliInfo, LoginTerminalExit } from "../../services/types";
+import {
+  launchClaudeCli,
+  closeClaudeTerminal,
+  detectClaudeCli,
+  startOauthLogin,
+} from "../../services/tauri-commands";
+import { LoginTerminal } from "../LoginTerminal";
 
-type ConnectionState = "idle" | "launching" | "polling";
+type ConnectionState = "idle" | "launching" | "polling" | "browser" | "terminal";
+
+const FALLBACK_CHECK_MS = 10_000;
 
 interface AccountConnectionStepProps {
   authStatus: AuthStatus | undefined;
@@ -22,20 +38,31 @@ export function AccountConnectionStep({
 }: AccountConnectionStepProps) {
   const [connectionState, setConnectionState] = useState<ConnectionState>("idle");
   const [error, setError] = useState<string | null>(null);
+  const [cli, setCli] = useState<ClaudeCliInfo | null>(null);
   const onRetryRef = useRef(onRetry);
   onRetryRef.current = onRetry;
 
   const isConnected = authStatus?.authenticated ?? false;
   const expiresAt = authStatus?.expires_at;
 
-  // Auto-continue when connected during polling
+  // The terminal logins need the CLI; the browser login works without it
+  useEffect(() => {
+    detectClaudeCli()
+      .then(setCli)
+      .catch(() => setCli(null));
+  }, []);
+
+  // Auto-continue when connected during polling or the embedded login; the
+  // embedded terminal stops its own child once it unmounts
   useEffect(() => {
-    if (isConnected && connectionState === "polling") {
+    if (isConnected && (connectionState === "polling" || connectionState === "terminal")) {
+      if (connectionState === "polling") {
+        // Close the terminal that was opened for login
+        closeClaudeTerminal().catch(() => {
+          // Ignore errors - terminal may already be closed
+        });
+      }
       setConnectionState("idle");
-      // Close the terminal that was opened for login
-      closeClaudeTerminal().catch(() => {
-        // Ignore errors - terminal may already be closed
-      });
       // Small delay to show success state before moving on
       setTimeout(() => {
         onContinue();
@@ -43,13 +70,14 @@ export function AccountConnectionStep({
     }
   }, [isConnected, connectionState, onContinue]);
 
-  // Polling effect
+  // `auth-changed` normally reports the login right away; Keychain logins
+  // touch no watched file, so keep a slow fallback check while waiting
   useEffect(() => {
     if (connectionState !== "polling") return;
 
     const interval = setInterval(() => {
       onRetryRef.current();
-    }, 2000);
+    }, FALLBACK_CHECK_MS);
 
     return () => clearInterval(interval);
   }, [connectionState]);
@@ -67,6 +95,35 @@ export function AccountConnectionStep({
     }
   }, []);
 
+  // In-app login: the backend opens the browser and waits for its redirect
+  const handleBrowserLogin = useCallback(async () => {
+    setError(null);
+    setConnectionState("browser");
+
+    try {
+      await startOauthLogin();
+      onRetryRef.current();
+    } catch (e) {
+      setError(typeof e === "string" ? e : "브라우저 로그인 실패");
+    } finally {
+      setConnectionState("idle");
+    }
+  }, []);
+
+  const handleTerminalExit = useCallback((exit: LoginTerminalExit) => {
+    if (exit.authenticated) {
+      onRetryRef.current();
+      return;
+    }
+    setConnectionState("idle");
+    setError("로그인이 완료되기 전에 claude가 종료되었습니다");
+  }, []);
+
+  const handleTerminalError = useCallback((message: string) => {
+    setConnectionState("idle");
+    setError(message);
+  }, []);
+
   const handleCancelPolling = useCallback(() => {
     setConnectionState("idle");
   }, []);
@@ -85,6 +142,9 @@ export function AccountConnectionStep({
 
   const isPolling = connectionState === "polling";
   const isLaunching = connectionState === "launching";
+  const isBrowserLogin = connectionState === "browser";
+  const isTerminal = connectionState === "terminal";
+  const cliMissing = cli?.missing ?? false;
 
   return (
     <div className="p-5 flex flex-col h-full">
@@ -101,8 +161,16 @@ export function AccountConnectionStep({
         </div>
       </div>
 
+      {/* Embedded login terminal */}
+      {isTerminal && !isConnected && (
+        <div className="flex-1 min-h-0 mb-4">
+          <LoginTerminal onExit={handleTerminalExit} onError={handleTerminalError} />
+        </div>
+      )}
+
       {/* Status Card */}
       <div
+        hidden={isTerminal && !isConnected}
         className={`p-4 rounded-lg border-2 mb-4 ${
           isConnected
             ? "bg-green-500/10 border-green-500/30"
@@ -111,7 +179,7 @@ export function AccountConnectionStep({
               : "bg-amber-500/10 border-amber-500/30"
         }`}
       >
-        {isLoading && !isPolling ? (
+        {isLoading && !isPolling && !isBrowserLogin ? (
           <div className="flex items-center gap-2 text-slate-400">
             <RefreshCw className="w-4 h-4 animate-spin" />
             <span className="text-sm">확인 중...</span>
@@ -128,6 +196,16 @@ export function AccountConnectionStep({
               </p>
             )}
           </>
+        ) : isBrowserLogin ? (
+          <>
+            <div className="flex items-center gap-2 text-blue-400 mb-2">
+              <RefreshCw className="w-5 h-5 animate-spin" />
+              <span className="text-sm font-medium">브라우저 로그인 대기 중...</span>
+            </div>
+            <p className="text-xs text-slate-400 ml-7">
+              열린 브라우저 창에서 로그인을 완료하면 자동으로 연결됩니다
+            </p>
+          </>
         ) : isPolling ? (
           <>
             <div className="flex items-center gap-2 text-blue-400 mb-2">
@@ -151,6 +229,26 @@ export function AccountConnectionStep({
         )}
       </div>
 
+      {/* Claude CLI status */}
+      {!isConnected && cliMissing && (
+        <div className="mb-4 p-3 bg-amber-500/10 border border-amber-500/30 rounded-lg text-xs text-amber-300">
+          <p className="mb-1.5">
+            Claude CLI를 찾을 수 없습니다. 브라우저로 로그인하거나 CLI를 설치하세요:
+          </p>
+          <code className="block bg-black/50 px-2 py-1 rounded text-green-400">
+            npm install -g @anthropic-ai/claude-code
+          </code>
+        </div>
+      )}
+      {!isConnected && cli?.outdated && (
+        <div className="mb-4 p-3 bg-amber-500/10 border border-amber-500/30 rounded-lg text-xs text-amber-300">
+          <p className="mb-1.5">
+            Claude CLI {cli.version} 버전은 오래되었습니다 (최소 {cli.min_version}). 업데이트하세요:
+          </p>
+          <code className="block bg-black/50 px-2 py-1 rounded text-green-400">claude update</code>
+        </div>
+      )}
+
       {/* Error Message */}
       {error && (
         <div className="mb-4 p-3 bg-red-500/10 border border-red-500/30 rounded-lg">
@@ -167,7 +265,7 @@ export function AccountConnectionStep({
           >
             다음 단계로
           </button>
-        ) : isPolling ? (
+        ) : isPolling || isTerminal ? (
           <button
             onClick={handleCancelPolling}
             className="w-full px-4 py-3 text-sm bg-slate-700 hover:bg-slate-600 text-slate-300 rounded-lg transition-colors"
@@ -177,19 +275,47 @@ export function AccountConnectionStep({
         ) : (
           <>
             <button
-              onClick={handleConnect}
-              disabled={isLaunching}
+              onClick={handleBrowserLogin}
+              disabled={isLaunching || isBrowserLogin}
               className="w-full flex items-center justify-center gap-2 px-4 py-3 text-sm bg-blue-600 hover:bg-blue-700 disabled:bg-slate-700 rounded-lg transition-colors font-medium"
             >
-              {isLaunching ? (
+              {isBrowserLogin ? (
                 <>
                   <RefreshCw className="w-4 h-4 animate-spin" />
+                  로그인 중...
+                </>
+              ) : (
+                <>
+                  <Globe className="w-4 h-4" />
+                  브라우저로 로그인
+                </>
+              )}
+            </button>
+            <button
+              onClick={() => {
+                setError(null);
+                setConnectionState("terminal");
+              }}
+              disabled={isLaunching || isBrowserLogin || cliMissing}
+              className="w-full flex items-center justify-center gap-2 px-4 py-3 text-sm bg-slate-700 hover:bg-slate-600 disabled:bg-slate-800 text-slate-300 rounded-lg transition-colors"
+            >
+              <Terminal className="w-4 h-4" />
+              터미널에서 연결하기
+            </button>
+            <button
+              onClick={handleConnect}
+              disabled={isLaunching || isBrowserLogin || cliMissing}
+              className="w-full flex items-center justify-center gap-1.5 text-xs text-slate-400 hover:text-slate-300 transition-colors"
+            >
+              {isLaunching ? (
+                <>
+                  <RefreshCw className="w-3 h-3 animate-spin" />
                   실행 중...
                 </>
               ) : (
                 <>
-                  <ExternalLink className="w-4 h-4" />
-                  연결하기
+                  <ExternalLink className="w-3 h-3" />
+                  외부 터미널 앱에서 열기
                 </>
               )}
             </button>
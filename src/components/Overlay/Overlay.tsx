@@ -1,12 +1,25 @@
 import { useUsageData } from "../../hooks/useUsageData";
+import { useProfiles } from "../../hooks/useProfiles";
 import { getUsageColor } from "../../utils/colors";
 
+// The compact overlay has room for three rows
+const OVERLAY_BUCKETS = ["five_hour", "seven_day", "seven_day_sonnet"];
+
 interface OverlayProps {
   enabled?: boolean;
 }
 
 export function Overlay({ enabled = true }: OverlayProps) {
-  const { data, isLoading, error } = useUsageData(enabled);
+  const { profiles, activeProfile, switchProfile } = useProfiles();
+  const { data, isLoading } = useUsageData(activeProfile, enabled);
+
+  // With several profiles, clicking the profile tag cycles through them
+  const profileIndex = profiles.findIndex((profile) => profile.id === activeProfile);
+  const currentProfile = profiles[profileIndex];
+  const cycleProfile = () => {
+    const next = profiles[(profileIndex + 1) % profiles.length];
+    if (next) switchProfile(next.id);
+  };
 
   if (isLoading) {
     return (
@@ -16,7 +29,8 @@ export function Overlay({ enabled = true }: OverlayProps) {
     );
   }
 
-  if (error || !data) {
+  // Keep showing the last known numbers during outages, greyed out
+  if (!data) {
     return (
       <div className="p-2 font-mono text-xs text-red-400">
         Error
@@ -24,18 +38,28 @@ export function Overlay({ enabled = true }: OverlayProps) {
     );
   }
 
-  const items = [
-    { label: "5H", value: data.five_hour.utilization },
-    { label: "7D", value: data.seven_day.utilization },
-    ...(data.seven_day_sonnet ? [{ label: "SNT", value: data.seven_day_sonnet.utilization }] : []),
-  ];
+  const items = data.buckets
+    .filter((bucket) => OVERLAY_BUCKETS.includes(bucket.key))
+    .map((bucket) => ({ label: bucket.short_label, value: bucket.metric.utilization }));
 
   return (
     <div
       data-tauri-drag-region
       className="p-2 select-none cursor-move"
     >
-      <div className="space-y-0.5 font-mono text-[11px] font-bold tracking-tight">
+      {profiles.length > 1 && currentProfile && (
+        <button
+          onClick={cycleProfile}
+          className="block mb-0.5 font-mono text-[9px] text-slate-500 hover:text-slate-300 truncate max-w-full"
+        >
+          {currentProfile.name}
+        </button>
+      )}
+      <div
+        className={`space-y-0.5 font-mono text-[11px] font-bold tracking-tight ${
+          data.stale_since ? "opacity-40 grayscale" : ""
+        }`}
+      >
         {items.map((item) => (
           <div key={item.label} className="flex items-center gap-2">
             <span className="text-slate-400 w-6">{item.label}</span>
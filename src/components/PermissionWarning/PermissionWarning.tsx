@@ -0,0 +1,73 @@
+import { useState } from "react";
+import { useQueryClient } from "@tanstack/react-query";
+import { AlertTriangle } from "lucide-react";
+import { fixCredentialsPermissions } from "../../services/tauri-commands";
+import type { AuthStatus, FilePermissionWarning } from "../../services/types";
+
+interface PermissionWarningProps {
+  warning: FilePermissionWarning;
+  profileId?: string;
+}
+
+const REASON_TEXT: Record<string, string> = {
+  world_readable: "is readable by every user",
+  group_readable: "is readable by your group",
+  wrong_owner: "is owned by another user",
+};
+
+export function PermissionWarning({ warning, profileId }: PermissionWarningProps) {
+  const queryClient = useQueryClient();
+  const [confirming, setConfirming] = useState(false);
+  const [error, setError] = useState<string | null>(null);
+
+  const handleFix = async () => {
+    try {
+      const status = await fixCredentialsPermissions(profileId);
+      queryClient.setQueryData<AuthStatus>(["auth", profileId ?? "active"], status);
+      setConfirming(false);
+    } catch (e) {
+      setError(String(e));
+    }
+  };
+
+  return (
+    <div className="bg-amber-900/20 border border-amber-700/50 rounded-lg p-2 text-[10px] text-amber-300">
+      <div className="flex items-start gap-1.5">
+        <AlertTriangle className="w-3 h-3 flex-shrink-0 mt-px" />
+        <p className="break-all">
+          {warning.path} {REASON_TEXT[warning.reason] ?? "has unsafe permissions"} (
+          {warning.mode})
+        </p>
+      </div>
+
+      {warning.fixable && !confirming && (
+        <button
+          onClick={() => setConfirming(true)}
+          className="mt-1.5 px-2 py-0.5 rounded bg-amber-700/50 hover:bg-amber-700 transition-colors"
+        >
+          Fix permissions
+        </button>
+      )}
+
+      {confirming && (
+        <div className="mt-1.5 flex items-center gap-1.5">
+          <span className="text-slate-300">Restrict to owner only (0600)?</span>
+          <button
+            onClick={handleFix}
+            className="px-2 py-0.5 rounded bg-amber-600 hover:bg-amber-500 text-black transition-colors"
+          >
+            Confirm
+          </button>
+          <button
+            onClick={() => setConfirming(false)}
+            className="px-2 py-0.5 rounded bg-slate-800 hover:bg-slate-700 transition-colors"
+          >
+            Cancel
+          </button>
+        </div>
+      )}
+
+      {error && <p className="mt-1 text-red-400">{error}</p>}
+    </div>
+  );
+}
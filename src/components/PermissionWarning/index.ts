@@ -0,0 +1 @@
+export { PermissionWarning } from "./PermissionWarning";
@@ -0,0 +1,38 @@
+import { useEffect, useState } from "react";
+import { AlertTriangle, X } from "lucide-react";
+import { takeSettingsRecovery } from "../../services/tauri-commands";
+import type { SettingsRecovery } from "../../services/types";
+
+/** Tells the user once that a corrupt settings file was set aside at startup */
+export function SettingsRecoveryNotice() {
+  const [recovery, setRecovery] = useState<SettingsRecovery | null>(null);
+
+  useEffect(() => {
+    takeSettingsRecovery()
+      .then(setRecovery)
+      .catch(() => setRecovery(null));
+  }, []);
+
+  if (!recovery) return null;
+
+  return (
+    <div className="m-1 bg-amber-900/20 border border-amber-700/50 rounded-lg p-2 text-[10px] text-amber-300">
+      <div className="flex items-start gap-1.5">
+        <AlertTriangle className="w-3 h-3 flex-shrink-0 mt-px" />
+        <p className="flex-1 break-all">
+          {recovery.restored_from_backup
+            ? "Settings were corrupt and have been restored from the last backup."
+            : "Settings were corrupt and have been reset."}{" "}
+          The damaged file was kept at {recovery.quarantined_path}
+        </p>
+        <button
+          onClick={() => setRecovery(null)}
+          className="text-amber-400 hover:text-amber-200 transition-colors"
+          aria-label="Dismiss"
+        >
+          <X className="w-3 h-3" />
+        </button>
+      </div>
+    </div>
+  );
+}
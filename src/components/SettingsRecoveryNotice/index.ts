@@ -0,0 +1 @@
+export { SettingsRecoveryNotice } from "./SettingsRecoveryNotice";
@@ -10,13 +10,13 @@ import {
 
 interface ShortcutSettingsProps {
   platformName: string;
-  onNext: (modifier: string, key: string) => void;
+  onNext: (modifier: string, key: string) => void | Promise<void>;
 }
 
 type ShortcutMode = "platform_default" | "custom";
 
 const PLATFORM_DEFAULTS: Record<string, { modifier: string; key: string; display: string }> = {
-  macos: { modifier: "super+shift", key: "u", display: "Cmd+Shift+U" },
+  macos: { modifier: "super+shift", key: "u", display: "⇧⌘U" },
   linux: { modifier: "ctrl+shift", key: "u", display: "Ctrl+Shift+U" },
   windows: { modifier: "alt", key: "r", display: "Alt+R" },
 };
@@ -28,6 +28,7 @@ export function ShortcutSettings({
   const [mode, setMode] = useState<ShortcutMode>("platform_default");
   const [customShortcut, setCustomShortcut] = useState<CapturedShortcut | null>(null);
   const [isCapturing, setIsCapturing] = useState(false);
+  const [saveError, setSaveError] = useState<string | null>(null);
 
   const { capturing, reset } = useKeyCapture({
     enabled: isCapturing,
@@ -61,20 +62,28 @@ export function ShortcutSettings({
   const isNextEnabled = useMemo(() => {
     if (mode === "platform_default") return true;
     if (!customShortcut) return false;
-    return customShortcut.modifiers.length > 0 && customShortcut.key.length === 1;
+    return customShortcut.code.length > 0;
   }, [mode, customShortcut]);
 
-  const handleNext = () => {
-    if (mode === "platform_default") {
-      onNext(platformDefault.modifier, platformDefault.key);
-    } else if (customShortcut) {
-      const backend = toBackendFormat(customShortcut);
-      onNext(backend.modifier, backend.key);
+  const handleNext = async () => {
+    const backend =
+      mode === "platform_default"
+        ? platformDefault
+        : customShortcut && toBackendFormat(customShortcut);
+    if (!backend) return;
+
+    setSaveError(null);
+    try {
+      // Rejected when another app already holds the shortcut
+      await onNext(backend.modifier, backend.key);
+    } catch (e) {
+      setSaveError(String(e));
     }
   };
 
   const handleModeChange = (newMode: ShortcutMode) => {
     setMode(newMode);
+    setSaveError(null);
     if (newMode === "platform_default") {
       setIsCapturing(false);
       reset();
@@ -164,7 +173,7 @@ export function ShortcutSettings({
         {mode === "custom" && (
           <div className="ml-6">
             <p className="text-[11px] text-slate-500 mb-2">
-              Modifier 키(Cmd/Ctrl/Alt/Shift) + 알파벳 키 조합만 지원됩니다
+              Modifier 키(Cmd/Ctrl/Alt/Shift) + 아무 키, 또는 F1–F24 단독으로 지정할 수 있습니다
             </p>
             <button
               onClick={handleStartCapture}
@@ -186,7 +195,9 @@ export function ShortcutSettings({
                       {formatModifierDisplay(m, platformName)}
                     </span>
                   ))}
-                  <span className="text-slate-400">+</span>
+                  {customShortcut.modifiers.length > 0 && (
+                    <span className="text-slate-400">+</span>
+                  )}
                   <span className="px-2 py-0.5 text-xs font-mono bg-slate-600 text-slate-200 rounded">
                     {customShortcut.key}
                   </span>
@@ -205,6 +216,9 @@ export function ShortcutSettings({
       </div>
 
       <div className="mt-4 space-y-3">
+        {saveError && (
+          <p className="text-[11px] text-red-400 text-center">{saveError}</p>
+        )}
         <button
           onClick={handleNext}
           disabled={!isNextEnabled}
@@ -1,3 +1,4 @@
+import { useEffect, useState } from "react";
 import type { UsageMetric } from "../../services/types";
 import { formatResetTime, formatPercentage } from "../../utils/formatters";
 import { getUsageColorClass, getUsageTextColorClass } from "../../utils/colors";
@@ -7,7 +8,17 @@ interface UsageCardProps {
   metric: UsageMetric;
 }
 
+// Re-render periodically so reset countdowns keep moving on cached data
+const COUNTDOWN_TICK_MS = 30_000;
+
 export function UsageCard({ title, metric }: UsageCardProps) {
+  const [, setTick] = useState(0);
+
+  useEffect(() => {
+    const interval = setInterval(() => setTick((t) => t + 1), COUNTDOWN_TICK_MS);
+    return () => clearInterval(interval);
+  }, []);
+
   const colorClass = getUsageColorClass(metric.utilization);
   const textColorClass = getUsageTextColorClass(metric.utilization);
 
@@ -1,11 +1,35 @@
-import { useQuery } from "@tanstack/react-query";
+import { useEffect } from "react";
+import { useQuery, useQueryClient } from "@tanstack/react-query";
+import { listen } from "@tauri-apps/api/event";
 import { checkCredentials } from "../services/tauri-commands";
+import type { AuthStatus } from "../services/types";
+
+export function useAuth(profileId?: string) {
+  const queryClient = useQueryClient();
+  const queryKey = ["auth", profileId ?? "active"];
+
+  // The backend watches the credentials files and pushes every change
+  useEffect(() => {
+    const key = ["auth", profileId ?? "active"];
+    const unlisten = listen<AuthStatus>("auth-changed", (event) => {
+      const current = queryClient.getQueryData<AuthStatus>(key);
+      const target = profileId ?? current?.profile_id;
+      if (target === undefined) {
+        queryClient.invalidateQueries({ queryKey: key });
+      } else if (event.payload.profile_id === target) {
+        queryClient.setQueryData(key, event.payload);
+      }
+    });
+
+    return () => {
+      unlisten.then((fn) => fn());
+    };
+  }, [queryClient, profileId]);
 
-export function useAuth() {
   return useQuery({
-    queryKey: ["auth"],
-    queryFn: checkCredentials,
-    staleTime: 30_000,
+    queryKey,
+    queryFn: () => checkCredentials(profileId),
+    staleTime: Infinity,
     retry: false,
   });
 }
@@ -0,0 +1,25 @@
+import { useEffect, useState } from "react";
+import { listen } from "@tauri-apps/api/event";
+import type { ExpiryWarning } from "../services/types";
+
+/// Latest `auth-expiring` warning for the profile, until that token expires or is replaced
+export function useExpiryWarning(profileId: string | undefined, expiresAt: number | null) {
+  const [warning, setWarning] = useState<ExpiryWarning | null>(null);
+
+  useEffect(() => {
+    const unlisten = listen<ExpiryWarning>("auth-expiring", (event) => {
+      if (event.payload.profile_id === profileId) {
+        setWarning(event.payload);
+      }
+    });
+
+    return () => {
+      unlisten.then((fn) => fn());
+    };
+  }, [profileId]);
+
+  if (!warning || warning.expires_at !== expiresAt || warning.expires_at < Date.now()) {
+    return null;
+  }
+  return warning;
+}
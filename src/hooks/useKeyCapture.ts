@@ -5,7 +5,21 @@ const MODIFIER_KEYS = ["Meta", "Control", "Alt", "Shift"];
 
 export interface CapturedShortcut {
   modifiers: string[];
+  /** Label for the key, e.g. `K`, `F5` or `ArrowUp` */
   key: string;
+  /** `KeyboardEvent.code` of the key, which the backend understands */
+  code: string;
+}
+
+/** `KeyK` → `K`, `Digit7` → `7`; other codes are their own label */
+function codeLabel(code: string): string {
+  const match = /^(?:Key|Digit)(.)$/.exec(code);
+  return match ? match[1] : code;
+}
+
+/** F-keys are rarely typed, so they work without a modifier */
+function isFunctionKey(code: string): boolean {
+  return /^F\d+$/.test(code);
 }
 
 interface UseKeyCaptureOptions {
@@ -16,19 +30,25 @@ interface UseKeyCaptureOptions {
 export function useKeyCapture({ enabled, onCapture }: UseKeyCaptureOptions) {
   const [capturing, setCapturing] = useState(false);
   const [result, setResult] = useState<CapturedShortcut | null>(null);
-  const pressedKeysRef = useRef<Set<string>>(new Set());
+  // `KeyboardEvent.code` → `KeyboardEvent.key` of everything held down
+  const pressedKeysRef = useRef<Map<string, string>>(new Map());
   const timerRef = useRef<ReturnType<typeof setTimeout> | null>(null);
   const captureStartedRef = useRef(false);
 
   const finalize = useCallback(() => {
-    const keys = Array.from(pressedKeysRef.current);
-    const modifiers = keys.filter((k) => MODIFIER_KEYS.includes(k));
-    const letterKeys = keys.filter((k) => /^[a-zA-Z]$/.test(k));
-
-    if (modifiers.length > 0 && letterKeys.length === 1) {
+    const pressed = Array.from(pressedKeysRef.current);
+    const modifiers = [
+      ...new Set(pressed.map(([, key]) => key).filter((k) => MODIFIER_KEYS.includes(k))),
+    ];
+    const codes = pressed
+      .filter(([, key]) => !MODIFIER_KEYS.includes(key))
+      .map(([code]) => code);
+
+    if (codes.length === 1 && (modifiers.length > 0 || isFunctionKey(codes[0]))) {
       const newResult: CapturedShortcut = {
         modifiers,
-        key: letterKeys[0].toUpperCase(),
+        key: codeLabel(codes[0]),
+        code: codes[0],
       };
       setResult(newResult);
       onCapture?.(newResult);
@@ -68,7 +88,7 @@ export function useKeyCapture({ enabled, onCapture }: UseKeyCaptureOptions) {
         setCapturing(true);
       }
 
-      pressedKeysRef.current.add(e.key);
+      pressedKeysRef.current.set(e.code, e.key);
 
       // Reset timer on each new key
       if (timerRef.current) {
@@ -105,9 +125,11 @@ export function toBackendFormat(shortcut: CapturedShortcut): {
   const mods = shortcut.modifiers.map((m) => modMap[m] || m.toLowerCase());
   const sorted = mods.sort((a, b) => order.indexOf(a) - order.indexOf(b));
 
+  // Letters and digits keep their old single-character form
+  const label = codeLabel(shortcut.code);
   return {
     modifier: sorted.join("+"),
-    key: shortcut.key.toLowerCase(),
+    key: label.length === 1 ? label.toLowerCase() : shortcut.code,
   };
 }
 
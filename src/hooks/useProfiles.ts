@@ -0,0 +1,27 @@
+import { useMutation, useQuery, useQueryClient } from "@tanstack/react-query";
+import { getProfiles, setActiveProfile } from "../services/tauri-commands";
+
+export function useProfiles() {
+  const queryClient = useQueryClient();
+
+  const query = useQuery({
+    queryKey: ["profiles"],
+    queryFn: getProfiles,
+    staleTime: Infinity,
+  });
+
+  const switchProfile = useMutation({
+    mutationFn: setActiveProfile,
+    onSuccess: () => {
+      queryClient.invalidateQueries({ queryKey: ["profiles"] });
+      queryClient.invalidateQueries({ queryKey: ["auth"] });
+    },
+  });
+
+  return {
+    profiles: query.data?.profiles ?? [],
+    activeProfile: query.data?.active_profile,
+    isLoading: query.isLoading,
+    switchProfile: switchProfile.mutate,
+  };
+}
@@ -1,12 +1,39 @@
-import { useQuery } from "@tanstack/react-query";
+import { useEffect, useState } from "react";
+import { useQuery, useQueryClient } from "@tanstack/react-query";
+import { listen } from "@tauri-apps/api/event";
 import { fetchUsageData } from "../services/tauri-commands";
+import type { ApiError, UsageData } from "../services/types";
 
-export function useUsageData(enabled: boolean = true) {
-  return useQuery({
-    queryKey: ["usage"],
-    queryFn: fetchUsageData,
-    refetchInterval: 10_000, // Auto-refresh every 10 seconds
-    staleTime: 5_000,
-    enabled,
+export function useUsageData(profileId: string | undefined, enabled: boolean = true) {
+  const queryClient = useQueryClient();
+  const [pushError, setPushError] = useState<Error | null>(null);
+
+  // The backend scheduler owns polling and pushes every profile's result to all windows
+  useEffect(() => {
+    if (!profileId) return;
+
+    const unlistenUpdated = listen<UsageData>("usage-updated", (event) => {
+      if (event.payload.profile_id !== profileId) return;
+      setPushError(null);
+      queryClient.setQueryData(["usage", profileId], event.payload);
+    });
+    const unlistenError = listen<ApiError>("usage-error", (event) => {
+      if (event.payload.profile_id !== profileId) return;
+      setPushError(new Error(event.payload.message));
+    });
+
+    return () => {
+      unlistenUpdated.then((fn) => fn());
+      unlistenError.then((fn) => fn());
+    };
+  }, [queryClient, profileId]);
+
+  const query = useQuery({
+    queryKey: ["usage", profileId],
+    queryFn: () => fetchUsageData(profileId),
+    staleTime: Infinity,
+    enabled: enabled && !!profileId,
   });
+
+  return { ...query, error: query.error ?? pushError };
 }
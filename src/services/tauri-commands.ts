@@ -1,18 +1,53 @@
 import { invoke } from "@tauri-apps/api/core";
-import type { UsageData, AuthStatus, LayoutType } from "./types";
+import type {
+  UsageData,
+  AuthStatus,
+  ClaudeCliInfo,
+  LayoutType,
+  NetworkConfig,
+  ConnectionReport,
+  ExpiryWarningConfig,
+  Profile,
+  ProfileList,
+  SettingsRecovery,
+} from "./types";
 
-export async function checkCredentials(): Promise<AuthStatus> {
-  return invoke("check_credentials");
+export async function checkCredentials(profileId?: string): Promise<AuthStatus> {
+  return invoke("check_credentials", { profileId });
 }
 
-export async function fetchUsageData(): Promise<UsageData> {
-  return invoke("fetch_usage_data");
+export async function fixCredentialsPermissions(profileId?: string): Promise<AuthStatus> {
+  return invoke("fix_credentials_permissions", { profileId });
+}
+
+export async function startOauthLogin(profileId?: string): Promise<AuthStatus> {
+  return invoke("start_oauth_login", { profileId });
+}
+
+export async function fetchUsageData(profileId?: string): Promise<UsageData> {
+  return invoke("fetch_usage_data", { profileId });
+}
+
+export async function getProfiles(): Promise<ProfileList> {
+  return invoke("get_profiles");
+}
+
+export async function saveProfiles(profiles: Profile[]): Promise<void> {
+  return invoke("save_profiles", { profiles });
+}
+
+export async function setActiveProfile(profileId: string): Promise<void> {
+  return invoke("set_active_profile", { profileId });
 }
 
 export async function getCredentialsPath(): Promise<string> {
   return invoke("get_credentials_path_cmd");
 }
 
+export async function detectClaudeCli(): Promise<ClaudeCliInfo> {
+  return invoke("detect_claude_cli");
+}
+
 export async function launchClaudeCli(): Promise<void> {
   return invoke("launch_claude_cli");
 }
@@ -21,6 +56,26 @@ export async function closeClaudeTerminal(): Promise<void> {
   return invoke("close_claude_terminal");
 }
 
+export async function startLoginTerminal(
+  cols: number,
+  rows: number,
+  profileId?: string
+): Promise<number> {
+  return invoke("start_login_terminal", { profileId, cols, rows });
+}
+
+export async function writeLoginTerminal(data: string): Promise<void> {
+  return invoke("write_login_terminal", { data });
+}
+
+export async function resizeLoginTerminal(cols: number, rows: number): Promise<void> {
+  return invoke("resize_login_terminal", { cols, rows });
+}
+
+export async function stopLoginTerminal(): Promise<void> {
+  return invoke("stop_login_terminal");
+}
+
 export async function setWindowSize(width: number, height: number): Promise<void> {
   return invoke("set_window_size", { width, height });
 }
@@ -28,3 +83,37 @@ export async function setWindowSize(width: number, height: number): Promise<void
 export async function saveLayoutSetting(layoutType: LayoutType): Promise<void> {
   return invoke("save_layout_setting", { layoutType });
 }
+
+export async function saveNetworkSetting(network: NetworkConfig): Promise<void> {
+  return invoke("save_network_setting", { network });
+}
+
+export async function saveExpiryWarningSetting(
+  expiryWarnings: ExpiryWarningConfig
+): Promise<void> {
+  return invoke("save_expiry_warning_setting", { expiryWarnings });
+}
+
+export async function saveCredentialsPathSetting(
+  credentialsPath: string | null
+): Promise<void> {
+  return invoke("save_credentials_path_setting", { credentialsPath });
+}
+
+export async function takeSettingsRecovery(): Promise<SettingsRecovery | null> {
+  return invoke("take_settings_recovery");
+}
+
+export async function takeMissingDirs(): Promise<string | null> {
+  return invoke("take_missing_dirs");
+}
+
+export async function saveTerminalCommandSetting(
+  terminalCommand: string | null
+): Promise<void> {
+  return invoke("save_terminal_command_setting", { terminalCommand });
+}
+
+export async function testConnection(): Promise<ConnectionReport> {
+  return invoke("test_connection");
+}
@@ -3,23 +3,84 @@ export interface UsageMetric {
   resets_at: string | null;
 }
 
+export interface UsageBucket {
+  key: string;
+  label: string;
+  short_label: string;
+  known: boolean;
+  metric: UsageMetric;
+}
+
 export interface UsageData {
-  five_hour: UsageMetric;
-  seven_day: UsageMetric;
+  profile_id?: string;
+  five_hour: UsageMetric | null;
+  seven_day: UsageMetric | null;
   seven_day_opus: UsageMetric | null;
   seven_day_sonnet: UsageMetric | null;
+  buckets: UsageBucket[];
+  fetched_at: number | null;
+  stale_since: number | null;
+}
+
+export interface ProviderAttempt {
+  provider: string;
+  location: string;
+  reason: string;
 }
 
 export interface AuthStatus {
+  profile_id: string;
   authenticated: boolean;
   expires_at: number | null;
   credentials_path: string;
   error_reason: string | null;
+  source: string | null;
+  skipped_providers: ProviderAttempt[];
+  permission_warning: FilePermissionWarning | null;
+  clock_skew_seconds: number | null;
+  missing_scopes: string[];
+}
+
+export interface ExpiryWarning {
+  profile_id: string;
+  profile_name: string;
+  expires_at: number;
+  minutes_left: number;
+  threshold_minutes: number;
+  clock_skew_seconds: number | null;
+}
+
+export interface ClaudeCliInfo {
+  missing: boolean;
+  path: string | null;
+  version: string | null;
+  outdated: boolean;
+  min_version: string;
+}
+
+export interface LoginTerminalOutput {
+  session_id: number;
+  data: string;
+}
+
+export interface LoginTerminalExit {
+  session_id: number;
+  exit_code: number | null;
+  authenticated: boolean;
+}
+
+export interface FilePermissionWarning {
+  path: string;
+  mode: string;
+  reason: "group_readable" | "world_readable" | "wrong_owner";
+  fixable: boolean;
 }
 
 export interface ApiError {
+  profile_id?: string;
   code: string;
   message: string;
+  retry_after: number | null;
 }
 
 export type LayoutType = "simple" | "detailed";
@@ -33,8 +94,76 @@ export interface ShortcutConfig {
   key: string;
 }
 
+export interface NetworkConfig {
+  proxy_url: string | null;
+  no_proxy: string | null;
+  ca_cert_paths: string[];
+}
+
+/** Overrides for the in-app login's OAuth endpoints */
+export interface OAuthEndpoints {
+  authorize_url?: string;
+  token_url?: string;
+}
+
+export type ConnectionStage = "config" | "dns" | "proxy" | "tls" | "http";
+
+export interface ConnectionCheck {
+  stage: ConnectionStage;
+  status: "ok" | "failed" | "skipped";
+  detail: string;
+}
+
+export interface ConnectionReport {
+  target: string;
+  proxy: string | null;
+  ok: boolean;
+  checks: ConnectionCheck[];
+}
+
+export type CredentialSource =
+  | { type: "env"; var: string }
+  | { type: "file"; path: string }
+  | { type: "default_file" }
+  | { type: "keychain" }
+  | { type: "secret_service"; service: string }
+  | { type: "process"; command: string };
+
+export interface Profile {
+  id: string;
+  name: string;
+  credential_providers: CredentialSource[];
+}
+
+export interface ProfileList {
+  profiles: Profile[];
+  active_profile: string;
+}
+
+export interface ExpiryWarningConfig {
+  minutes_before: number[];
+  desktop_notifications: boolean;
+}
+
+export interface SettingsRecovery {
+  quarantined_path: string;
+  restored_from_backup: boolean;
+  error: string;
+}
+
 export interface AppSettings {
+  /** Settings file format, upgraded by the backend on load */
+  schema_version: number;
   shortcut: ShortcutConfig;
   first_launch: boolean;
   layout: LayoutConfig;
+  api_base_url?: string | null;
+  network: NetworkConfig;
+  oauth: OAuthEndpoints;
+  credentials_path?: string | null;
+  profiles: Profile[];
+  active_profile?: string | null;
+  expiry_warnings: ExpiryWarningConfig;
+  /** External terminal for the login, e.g. `kitty --title {title} -e {cmd}` */
+  terminal_command?: string | null;
 }
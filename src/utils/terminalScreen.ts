@@ -0,0 +1,234 @@
+/**
+ * Minimal VT100 screen model for the embedded login terminal. It covers the
+ * cursor movement and erase sequences the `claude` CLI uses to redraw its
+ * prompts; colors and other modes are ignored.
+ */
+
+const MAX_SCROLLBACK = 500;
+
+type ParseState = "text" | "escape" | "csi" | "osc";
+
+export class TerminalScreen {
+  private rows: string[][];
+  private scrollback: string[] = [];
+  private row = 0;
+  private col = 0;
+  private state: ParseState = "text";
+  private sequence = "";
+
+  constructor(
+    private cols: number,
+    private height: number,
+  ) {
+    this.rows = Array.from({ length: height }, () => []);
+  }
+
+  resize(cols: number, height: number) {
+    this.cols = cols;
+    while (this.rows.length > height) {
+      this.scrollUp();
+      this.row = Math.max(0, this.row - 1);
+    }
+    while (this.rows.length < height) {
+      this.rows.push([]);
+    }
+    this.height = height;
+    this.col = Math.min(this.col, cols - 1);
+  }
+
+  write(data: string) {
+    for (const ch of data) {
+      switch (this.state) {
+        case "text":
+          this.text(ch);
+          break;
+        case "escape":
+          if (ch === "[") {
+            this.state = "csi";
+            this.sequence = "";
+          } else if (ch === "]") {
+            this.state = "osc";
+          } else {
+            // Two-character sequences such as charset selection: nothing to draw
+            this.state = "text";
+          }
+          break;
+        case "csi":
+          if (ch >= "@" && ch <= "~") {
+            this.csi(this.sequence, ch);
+            this.state = "text";
+          } else {
+            this.sequence += ch;
+          }
+          break;
+        case "osc":
+          // Window titles and hyperlinks end with BEL or ST (ESC \)
+          if (ch === "\x07") {
+            this.state = "text";
+          } else if (ch === "\x1b") {
+            this.state = "escape";
+          }
+          break;
+      }
+    }
+  }
+
+  /** Scrollback followed by the visible screen, trailing blank lines removed */
+  lines(): string[] {
+    const all = [...this.scrollback, ...this.rows.map((cells) => cells.join(""))];
+    while (all.length > 0 && all[all.length - 1].trim() === "") {
+      all.pop();
+    }
+    return all;
+  }
+
+  private text(ch: string) {
+    switch (ch) {
+      case "\x1b":
+        this.state = "escape";
+        return;
+      case "\r":
+        this.col = 0;
+        return;
+      case "\n":
+        this.lineFeed();
+        return;
+      case "\b":
+        this.col = Math.max(0, this.col - 1);
+        return;
+      case "\t":
+        this.col = Math.min(this.cols - 1, (Math.floor(this.col / 8) + 1) * 8);
+        return;
+      case "\x07":
+        return;
+    }
+    if (ch < " ") return;
+
+    if (this.col >= this.cols) {
+      this.col = 0;
+      this.lineFeed();
+    }
+    const cells = this.rows[this.row];
+    while (cells.length < this.col) cells.push(" ");
+    cells[this.col] = ch;
+    this.col += 1;
+  }
+
+  private csi(params: string, command: string) {
+    // Private modes (`?25l` and friends) only toggle things we don't draw
+    if (params.startsWith("?") || params.startsWith(">")) return;
+
+    const args = params.split(";").map((p) => parseInt(p, 10));
+    const n = (index: number, fallback = 1) =>
+      Number.isNaN(args[index]) || args[index] === undefined ? fallback : args[index];
+
+    switch (command) {
+      case "A":
+        this.row = Math.max(0, this.row - n(0));
+        break;
+      case "B":
+        this.row = Math.min(this.height - 1, this.row + n(0));
+        break;
+      case "C":
+        this.col = Math.min(this.cols - 1, this.col + n(0));
+        break;
+      case "D":
+        this.col = Math.max(0, this.col - n(0));
+        break;
+      case "E":
+        this.row = Math.min(this.height - 1, this.row + n(0));
+        this.col = 0;
+        break;
+      case "F":
+        this.row = Math.max(0, this.row - n(0));
+        this.col = 0;
+        break;
+      case "G":
+        this.col = Math.min(this.cols - 1, n(0) - 1);
+        break;
+      case "H":
+      case "f":
+        this.row = Math.min(this.height - 1, n(0) - 1);
+        this.col = Math.min(this.cols - 1, n(1) - 1);
+        break;
+      case "J":
+        this.eraseDisplay(n(0, 0));
+        break;
+      case "K":
+        this.eraseLine(n(0, 0));
+        break;
+    }
+  }
+
+  private eraseLine(mode: number) {
+    const cells = this.rows[this.row];
+    if (mode === 0) {
+      cells.length = Math.min(cells.length, this.col);
+    } else if (mode === 1) {
+      for (let i = 0; i <= this.col && i < cells.length; i++) cells[i] = " ";
+    } else {
+      this.rows[this.row] = [];
+    }
+  }
+
+  private eraseDisplay(mode: number) {
+    if (mode === 0) {
+      this.eraseLine(0);
+      for (let r = this.row + 1; r < this.height; r++) this.rows[r] = [];
+    } else if (mode === 1) {
+      this.eraseLine(1);
+      for (let r = 0; r < this.row; r++) this.rows[r] = [];
+    } else {
+      this.rows = Array.from({ length: this.height }, () => []);
+    }
+  }
+
+  private lineFeed() {
+    if (this.row < this.height - 1) {
+      this.row += 1;
+    } else {
+      this.scrollUp();
+    }
+  }
+
+  private scrollUp() {
+    const top = this.rows.shift();
+    this.rows.push([]);
+    if (top) {
+      this.scrollback.push(top.join(""));
+      if (this.scrollback.length > MAX_SCROLLBACK) this.scrollback.shift();
+    }
+  }
+}
+
+/** Bytes a terminal would send for a key press, or null to let the browser handle it */
+export function keyToInput(event: {
+  key: string;
+  ctrlKey: boolean;
+  altKey: boolean;
+  metaKey: boolean;
+}): string | null {
+  if (event.metaKey) return null;
+
+  const special: Record<string, string> = {
+    Enter: "\r",
+    Backspace: "\x7f",
+    Tab: "\t",
+    Escape: "\x1b",
+    ArrowUp: "\x1b[A",
+    ArrowDown: "\x1b[B",
+    ArrowRight: "\x1b[C",
+    ArrowLeft: "\x1b[D",
+    Home: "\x1b[H",
+    End: "\x1b[F",
+    Delete: "\x1b[3~",
+  };
+  if (event.key in special) return special[event.key];
+
+  if (event.key.length !== 1) return null;
+  if (event.ctrlKey) {
+    const code = event.key.toLowerCase().charCodeAt(0);
+    return code >= 97 && code <= 122 ? String.fromCharCode(code - 96) : null;
+  }
+  return event.altKey ? `\x1b${event.key}` : event.key;
+}